@@ -0,0 +1,256 @@
+//! Derive macro for the `err-with` crate. See the `ErrWith` derive
+//! documentation in `err-with` for usage.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{quote, quote_spanned};
+use syn::{
+  meta::ParseNestedMeta, parse_macro_input, parse_quote, spanned::Spanned, Data, DeriveInput,
+  Error, Fields, GenericArgument, Member, PathArguments, Result, Token, Type, Variant,
+};
+
+#[proc_macro_derive(ErrWith, attributes(err_with))]
+pub fn derive_err_with(input: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as DeriveInput);
+
+  match expand(&input) {
+    Ok(tokens) => tokens.into(),
+    Err(error) => error.to_compile_error().into(),
+  }
+}
+
+fn expand(input: &DeriveInput) -> Result<TokenStream2> {
+  let data = match &input.data {
+    Data::Enum(data) => data,
+    _ => {
+      return Err(Error::new(
+        Span::call_site(),
+        "`ErrWith` can only be derived for enums",
+      ))
+    }
+  };
+
+  let mut impls = TokenStream2::new();
+
+  for variant in &data.variants {
+    for attr in &variant.attrs {
+      if attr.path().is_ident("err_with") {
+        let options = Options::parse(attr)?;
+        impls.extend(expand_variant(input, variant, options)?);
+      }
+    }
+  }
+
+  Ok(impls)
+}
+
+#[derive(Default)]
+struct Options {
+  source: Option<Field>,
+  context: Option<Field>,
+  via: Option<syn::Path>,
+}
+
+/// A field selected with `key = field` or `key = field: Type`.
+struct Field {
+  member: Member,
+  ty: Option<Type>,
+}
+
+impl Field {
+  fn parse(meta: &ParseNestedMeta) -> Result<Field> {
+    let input = meta.value()?;
+    let member = input.parse()?;
+
+    let ty = if input.peek(Token![:]) {
+      input.parse::<Token![:]>()?;
+      Some(input.parse()?)
+    } else {
+      None
+    };
+
+    Ok(Field { member, ty })
+  }
+}
+
+impl Options {
+  fn parse(attr: &syn::Attribute) -> Result<Options> {
+    let mut options = Options::default();
+
+    // A bare `#[err_with]` selects the default fields.
+    if let syn::Meta::Path(_) = attr.meta {
+      return Ok(options);
+    }
+
+    let mut keys = Vec::new();
+
+    attr.parse_nested_meta(|meta| {
+      if let Some(key) = meta.path.get_ident() {
+        if keys.contains(key) {
+          return Err(meta.error(format!("duplicate `{}` key", key)));
+        }
+        keys.push(key.clone());
+      }
+
+      if meta.path.is_ident("source") {
+        // A bare `source` marks the remaining field as the source, which
+        // is also the default.
+        if meta.input.peek(Token![=]) {
+          options.source = Some(Field::parse(&meta)?);
+        }
+      } else if meta.path.is_ident("context") {
+        options.context = Some(Field::parse(&meta)?);
+      } else if meta.path.is_ident("via") {
+        options.via = Some(meta.value()?.parse()?);
+      } else {
+        return Err(meta.error("expected `source`, `context`, or `via`"));
+      }
+      Ok(())
+    })?;
+
+    Ok(options)
+  }
+}
+
+fn expand_variant(
+  input: &DeriveInput,
+  variant: &Variant,
+  options: Options,
+) -> Result<TokenStream2> {
+  let fields: Vec<(Member, &Type)> = match &variant.fields {
+    Fields::Named(fields) => fields
+      .named
+      .iter()
+      .map(|field| (Member::Named(field.ident.clone().unwrap()), &field.ty))
+      .collect(),
+    Fields::Unnamed(fields) => fields
+      .unnamed
+      .iter()
+      .enumerate()
+      .map(|(i, field)| (Member::from(i), &field.ty))
+      .collect(),
+    Fields::Unit => Vec::new(),
+  };
+
+  if fields.len() != 2 {
+    return Err(Error::new_spanned(
+      variant,
+      "`err_with` variants must have exactly two fields, a source and a context",
+    ));
+  }
+
+  let (source, context) = match (&options.source, &options.context) {
+    (Some(source), Some(context)) => {
+      if source.member == context.member {
+        return Err(Error::new_spanned(
+          &context.member,
+          "the source and context must be different fields",
+        ));
+      }
+      (source.member.clone(), context.member.clone())
+    }
+    (Some(source), None) => {
+      let context = other(&fields, &source.member, variant)?;
+      (source.member.clone(), context)
+    }
+    (None, Some(context)) => {
+      let source = other(&fields, &context.member, variant)?;
+      (source, context.member.clone())
+    }
+    (None, None) => (fields[0].0.clone(), fields[1].0.clone()),
+  };
+
+  let source_ty = field_type(&fields, &source, variant)?;
+  let context_ty = field_type(&fields, &context, variant)?;
+
+  let checks = [(&options.source, source_ty), (&options.context, context_ty)]
+    .iter()
+    .filter_map(|(field, actual)| {
+      let expected = field.as_ref()?.ty.as_ref()?;
+      Some(check_type(expected, actual))
+    })
+    .collect::<Vec<TokenStream2>>();
+
+  let name = &input.ident;
+  let variant_name = &variant.ident;
+  let mut generics = input.generics.clone();
+  let (_, ty_generics, _) = input.generics.split_for_impl();
+
+  let (with_ty, convert) = match &options.via {
+    None => (quote!(#context_ty), quote!(context)),
+    Some(via) => {
+      let segment = via.segments.last().unwrap();
+
+      let target = match &segment.arguments {
+        PathArguments::AngleBracketed(arguments) if arguments.args.len() == 1 => {
+          match &arguments.args[0] {
+            GenericArgument::Type(target) => target,
+            _ => return Err(Error::new_spanned(via, "expected `AsRef<T>` or `Into<T>`")),
+          }
+        }
+        _ => return Err(Error::new_spanned(via, "expected `AsRef<T>` or `Into<T>`")),
+      };
+
+      let convert = if segment.ident == "AsRef" {
+        quote!(::core::convert::AsRef::<#target>::as_ref(&context).to_owned())
+      } else if segment.ident == "Into" {
+        quote!(::core::convert::Into::<#target>::into(context))
+      } else {
+        return Err(Error::new_spanned(via, "expected `AsRef<T>` or `Into<T>`"));
+      };
+
+      generics.params.push(parse_quote!(__W: #via));
+
+      (quote!(__W), convert)
+    }
+  };
+
+  let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+  Ok(quote! {
+    impl #impl_generics ::core::convert::From<(#source_ty, #with_ty)> for #name #ty_generics
+      #where_clause
+    {
+      fn from((source, context): (#source_ty, #with_ty)) -> Self {
+        #(#checks)*
+        #name::#variant_name {
+          #source: source,
+          #context: #convert,
+        }
+      }
+    }
+  })
+}
+
+/// Annotated field types are checked by the compiler, rather than by
+/// comparing tokens, so that equivalent spellings are accepted.
+fn check_type(expected: &Type, actual: &Type) -> TokenStream2 {
+  quote_spanned! {expected.span()=>
+    let _: ::core::marker::PhantomData<#expected> = ::core::marker::PhantomData::<#actual>;
+  }
+}
+
+fn other(fields: &[(Member, &Type)], member: &Member, variant: &Variant) -> Result<Member> {
+  field_type(fields, member, variant)?;
+
+  Ok(
+    fields
+      .iter()
+      .map(|(other, _)| other)
+      .find(|other| *other != member)
+      .unwrap()
+      .clone(),
+  )
+}
+
+fn field_type<'a>(
+  fields: &[(Member, &'a Type)],
+  member: &Member,
+  variant: &Variant,
+) -> Result<&'a Type> {
+  fields
+    .iter()
+    .find(|(field, _)| field == member)
+    .map(|(_, ty)| *ty)
+    .ok_or_else(|| Error::new_spanned(variant, "no such field"))
+}
@@ -0,0 +1,16 @@
+use err_with_derive::ErrWith;
+
+#[derive(Debug, ErrWith)]
+enum Error<E> {
+  #[err_with(via = Into<String>)]
+  Other(E, String),
+}
+
+fn main() {
+  match Error::from((7u8, "seven")) {
+    Error::Other(source, context) => {
+      assert_eq!(source, 7);
+      assert_eq!(context, "seven");
+    }
+  }
+}
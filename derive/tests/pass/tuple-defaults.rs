@@ -0,0 +1,20 @@
+use std::io;
+
+use err_with_derive::ErrWith;
+
+#[derive(Debug, ErrWith)]
+enum Error {
+  #[err_with]
+  Io(io::Error, &'static str),
+}
+
+fn main() {
+  let error = Error::from((io::Error::from(io::ErrorKind::NotFound), "foo/bar"));
+
+  match error {
+    Error::Io(error, path) => {
+      assert_eq!(error.kind(), io::ErrorKind::NotFound);
+      assert_eq!(path, "foo/bar");
+    }
+  }
+}
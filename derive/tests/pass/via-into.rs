@@ -0,0 +1,17 @@
+use std::num::ParseIntError;
+
+use err_with_derive::ErrWith;
+
+#[derive(Debug, ErrWith)]
+enum Error {
+  #[err_with(source, context = input: String, via = Into<String>)]
+  Parse { input: String, error: ParseIntError },
+}
+
+fn main() {
+  let error = "x".parse::<u8>().unwrap_err();
+
+  match Error::from((error, "x")) {
+    Error::Parse { input, .. } => assert_eq!(input, "x"),
+  }
+}
@@ -0,0 +1,9 @@
+use err_with_derive::ErrWith;
+
+#[derive(ErrWith)]
+enum Error {
+  #[err_with(context = path, context = message)]
+  Io { io_error: std::io::Error, path: String, message: String },
+}
+
+fn main() {}
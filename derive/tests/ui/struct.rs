@@ -0,0 +1,9 @@
+use err_with_derive::ErrWith;
+
+#[derive(ErrWith)]
+struct Error {
+  io_error: std::io::Error,
+  path: String,
+}
+
+fn main() {}
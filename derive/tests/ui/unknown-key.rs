@@ -0,0 +1,9 @@
+use err_with_derive::ErrWith;
+
+#[derive(ErrWith)]
+enum Error {
+  #[err_with(path)]
+  Io(std::io::Error, String),
+}
+
+fn main() {}
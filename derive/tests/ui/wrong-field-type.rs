@@ -0,0 +1,11 @@
+use std::path::PathBuf;
+
+use err_with_derive::ErrWith;
+
+#[derive(ErrWith)]
+enum Error {
+  #[err_with(source, context = path: String)]
+  Io { io_error: std::io::Error, path: PathBuf },
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use std::error::Error;
+
+use err_with::{report::ExitReport, ErrWith};
+
+fn run() -> Result<(), Box<dyn Error>> {
+  "eighty"
+    .parse::<u16>()
+    .err_with_contextual("parsing port")?;
+  Ok(())
+}
+
+fn main() -> ExitReport<Box<dyn Error>> {
+  run().into()
+}
@@ -0,0 +1,12 @@
+use std::{fs, io};
+
+use err_with::{report::ExitReport, Contextual, ErrWith};
+
+fn run() -> Result<(), Contextual<io::Error, &'static str>> {
+  fs::read("does/not/exist").err_with_contextual("loading configuration")?;
+  Ok(())
+}
+
+fn main() -> ExitReport<Contextual<io::Error, &'static str>> {
+  run().into()
+}
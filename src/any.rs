@@ -0,0 +1,189 @@
+use std::{any::Any, boxed::Box, error::Error, fmt};
+
+use crate::ErrWith;
+
+/// A type-erased error with a type-erased context, which can be
+/// recovered by type with `find_context` after the error has been
+/// boxed, or wrapped in other errors. Produced by
+/// `ErrWithAny::err_with_any` and `ErrWithAny::err_with_any_debug`, and
+/// enabled by the `std` feature.
+///
+/// The context is rendered with the `Display` or `Debug`
+/// implementation chosen when the `AnyContextual` was created:
+///
+/// ```
+/// use std::{error::Error, fs, path::{Path, PathBuf}};
+///
+/// use err_with::{AnyContextual, ErrWithAny, FindContext};
+///
+/// fn read() -> Result<Vec<u8>, AnyContextual> {
+///   fs::read("foo/bar").err_with_any_debug(PathBuf::from("foo/bar"))
+/// }
+///
+/// fn load() -> Result<Vec<u8>, Box<dyn Error>> {
+///   Ok(read().err_with_any("loading")?)
+/// }
+///
+/// let error = load().unwrap_err();
+/// assert_eq!(error.to_string(), "loading");
+/// assert_eq!(error.source().unwrap().to_string(), "\"foo/bar\"");
+/// assert_eq!(error.find_context::<PathBuf>().unwrap(), Path::new("foo/bar"));
+/// assert!(error.find_context::<String>().is_none());
+/// ```
+pub struct AnyContextual {
+  error: Box<dyn Error + Send + Sync>,
+  context: Box<dyn Any + Send + Sync>,
+  render: Render,
+}
+
+type Render = fn(&(dyn Any + Send + Sync), &mut fmt::Formatter) -> fmt::Result;
+
+fn display<W: fmt::Display + 'static>(
+  context: &(dyn Any + Send + Sync),
+  f: &mut fmt::Formatter,
+) -> fmt::Result {
+  fmt::Display::fmt(context.downcast_ref::<W>().unwrap(), f)
+}
+
+fn debug<W: fmt::Debug + 'static>(
+  context: &(dyn Any + Send + Sync),
+  f: &mut fmt::Formatter,
+) -> fmt::Result {
+  fmt::Debug::fmt(context.downcast_ref::<W>().unwrap(), f)
+}
+
+impl AnyContextual {
+  /// An `AnyContextual` which renders `context` with `Display`.
+  pub fn new<E, W>(error: E, context: W) -> AnyContextual
+  where
+    E: Error + Send + Sync + 'static,
+    W: fmt::Display + Send + Sync + 'static,
+  {
+    AnyContextual {
+      error: Box::new(error),
+      context: Box::new(context),
+      render: display::<W>,
+    }
+  }
+
+  /// An `AnyContextual` which renders `context` with `Debug`, for
+  /// contexts like `PathBuf` which do not implement `Display`.
+  pub fn new_debug<E, W>(error: E, context: W) -> AnyContextual
+  where
+    E: Error + Send + Sync + 'static,
+    W: fmt::Debug + Send + Sync + 'static,
+  {
+    AnyContextual {
+      error: Box::new(error),
+      context: Box::new(context),
+      render: debug::<W>,
+    }
+  }
+
+  pub fn error(&self) -> &(dyn Error + Send + Sync + 'static) {
+    self.error.as_ref()
+  }
+
+  /// The context, if it is of type `W`.
+  pub fn context<W: Any>(&self) -> Option<&W> {
+    self.context.downcast_ref()
+  }
+}
+
+impl fmt::Debug for AnyContextual {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_struct("AnyContextual")
+      .field("error", &self.error)
+      .field("context", &format_args!("{}", self))
+      .finish()
+  }
+}
+
+impl fmt::Display for AnyContextual {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    (self.render)(self.context.as_ref(), f)
+  }
+}
+
+impl Error for AnyContextual {
+  fn source(&self) -> Option<&(dyn Error + 'static)> {
+    Some(self.error.as_ref())
+  }
+}
+
+/// Extension trait for `ErrWith` implementors, enabled by the `std`
+/// feature.
+pub trait ErrWithAny<T, E> {
+  /// Attach `with` in an `AnyContextual`, from which it can be
+  /// recovered by type with `find_context`, even after the error has
+  /// been boxed or wrapped.
+  fn err_with_any<W>(self, with: W) -> Result<T, AnyContextual>
+  where
+    E: Error + Send + Sync + 'static,
+    W: fmt::Display + Send + Sync + 'static;
+
+  /// Like `err_with_any`, but for contexts which are rendered with
+  /// `Debug`.
+  fn err_with_any_debug<W>(self, with: W) -> Result<T, AnyContextual>
+  where
+    E: Error + Send + Sync + 'static,
+    W: fmt::Debug + Send + Sync + 'static;
+}
+
+impl<T, E, R: ErrWith<T, E>> ErrWithAny<T, E> for R {
+  fn err_with_any<W>(self, with: W) -> Result<T, AnyContextual>
+  where
+    E: Error + Send + Sync + 'static,
+    W: fmt::Display + Send + Sync + 'static,
+  {
+    self
+      .err_with(with)
+      .map_err(|(error, with)| AnyContextual::new(error, with))
+  }
+
+  fn err_with_any_debug<W>(self, with: W) -> Result<T, AnyContextual>
+  where
+    E: Error + Send + Sync + 'static,
+    W: fmt::Debug + Send + Sync + 'static,
+  {
+    self
+      .err_with(with)
+      .map_err(|(error, with)| AnyContextual::new_debug(error, with))
+  }
+}
+
+/// Find the first context of type `W` attached by an `AnyContextual` in
+/// the source chain of `error`, starting with `error` itself.
+pub fn find_context<'a, W: Any>(error: &'a (dyn Error + 'static)) -> Option<&'a W> {
+  let mut source = Some(error);
+
+  while let Some(error) = source {
+    if let Some(context) = error
+      .downcast_ref::<AnyContextual>()
+      .and_then(AnyContextual::context)
+    {
+      return Some(context);
+    }
+
+    source = error.source();
+  }
+
+  None
+}
+
+/// Extension trait for `dyn Error`, calling `find_context`.
+pub trait FindContext {
+  fn find_context<W: Any>(&self) -> Option<&W>;
+}
+
+impl FindContext for dyn Error + 'static {
+  fn find_context<W: Any>(&self) -> Option<&W> {
+    find_context(self)
+  }
+}
+
+impl FindContext for dyn Error + Send + Sync + 'static {
+  fn find_context<W: Any>(&self) -> Option<&W> {
+    find_context(self)
+  }
+}
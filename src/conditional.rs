@@ -0,0 +1,64 @@
+use core::fmt;
+
+#[cfg(feature = "std")]
+use std::error::Error;
+
+/// An error which may or may not have a context attached, produced by
+/// `ErrWith::err_with_if`.
+///
+/// `Display` and `Error` behave like those of `Contextual` when a
+/// context is attached, and like those of `E` otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Conditional<E, W> {
+  /// An error without a context.
+  Plain(E),
+  /// An error with a context.
+  With(E, W),
+}
+
+impl<E, W> Conditional<E, W> {
+  pub fn error(&self) -> &E {
+    match self {
+      Conditional::Plain(error) | Conditional::With(error, _) => error,
+    }
+  }
+
+  pub fn context(&self) -> Option<&W> {
+    match self {
+      Conditional::Plain(_) => None,
+      Conditional::With(_, with) => Some(with),
+    }
+  }
+
+  /// Discard the context, if any.
+  pub fn into_error(self) -> E {
+    match self {
+      Conditional::Plain(error) | Conditional::With(error, _) => error,
+    }
+  }
+}
+
+impl<E, W> From<(E, W)> for Conditional<E, W> {
+  fn from((error, with): (E, W)) -> Conditional<E, W> {
+    Conditional::With(error, with)
+  }
+}
+
+impl<E: fmt::Display, W: fmt::Display> fmt::Display for Conditional<E, W> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Conditional::Plain(error) => fmt::Display::fmt(error, f),
+      Conditional::With(_, with) => fmt::Display::fmt(with, f),
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl<E: Error + 'static, W: fmt::Display + fmt::Debug> Error for Conditional<E, W> {
+  fn source(&self) -> Option<&(dyn Error + 'static)> {
+    match self {
+      Conditional::Plain(error) => error.source(),
+      Conditional::With(error, _) => Some(error),
+    }
+  }
+}
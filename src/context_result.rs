@@ -0,0 +1,66 @@
+use crate::Contextual;
+
+/// Extension trait for results which already carry a context, such as
+/// those produced by `err_with`, for inspecting and transforming the
+/// error and context separately:
+///
+/// ```
+/// use err_with::{ContextResultExt, ErrWith};
+///
+/// let result: Result<(), (&str, &str)> = Err("failed").err_with("foo");
+///
+/// assert_eq!(result.context(), Some(&"foo"));
+/// assert_eq!(result.inner_err(), Some(&"failed"));
+///
+/// let result = result
+///   .map_context(|context| format!("{}/bar", context))
+///   .map_inner_err(str::len);
+///
+/// assert_eq!(result, Err((6, "foo/bar".to_owned())));
+/// assert_eq!(result.strip_context(), Err(6));
+/// ```
+pub trait ContextResultExt<T, E, W> {
+  /// Transform the context, leaving the error unchanged.
+  fn map_context<V, F: FnOnce(W) -> V>(self, f: F) -> Result<T, (E, V)>;
+
+  /// Transform the error, leaving the context unchanged.
+  fn map_inner_err<D, F: FnOnce(E) -> D>(self, f: F) -> Result<T, (D, W)>;
+
+  /// The context, if `self` is an error.
+  fn context(&self) -> Option<&W>;
+
+  /// The error without its context, if `self` is an error.
+  fn inner_err(&self) -> Option<&E>;
+
+  /// Discard the context.
+  fn strip_context(self) -> Result<T, E>;
+
+  /// Convert the `(E, W)` tuple into a `Contextual`.
+  fn into_contextual(self) -> Result<T, Contextual<E, W>>;
+}
+
+impl<T, E, W> ContextResultExt<T, E, W> for Result<T, (E, W)> {
+  fn map_context<V, F: FnOnce(W) -> V>(self, f: F) -> Result<T, (E, V)> {
+    self.map_err(|(error, with)| (error, f(with)))
+  }
+
+  fn map_inner_err<D, F: FnOnce(E) -> D>(self, f: F) -> Result<T, (D, W)> {
+    self.map_err(|(error, with)| (f(error), with))
+  }
+
+  fn context(&self) -> Option<&W> {
+    self.as_ref().err().map(|(_, with)| with)
+  }
+
+  fn inner_err(&self) -> Option<&E> {
+    self.as_ref().err().map(|(error, _)| error)
+  }
+
+  fn strip_context(self) -> Result<T, E> {
+    self.map_err(|(error, _)| error)
+  }
+
+  fn into_contextual(self) -> Result<T, Contextual<E, W>> {
+    self.map_err(Contextual::from)
+  }
+}
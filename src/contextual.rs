@@ -0,0 +1,66 @@
+use core::fmt;
+
+#[cfg(feature = "std")]
+use std::error::Error;
+
+/// An error `E` with a context `W` attached.
+///
+/// Unlike the `(E, W)` tuple produced by `err_with`, `Contextual`
+/// implements `Display`, rendering the context, and, with the `std`
+/// feature, `Error`, with `source` returning the inner error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Contextual<E, W> {
+  pub error: E,
+  pub context: W,
+}
+
+impl<E, W> Contextual<E, W> {
+  pub fn new(error: E, context: W) -> Contextual<E, W> {
+    Contextual { error, context }
+  }
+
+  pub fn into_tuple(self) -> (E, W) {
+    (self.error, self.context)
+  }
+}
+
+impl<E, W> From<(E, W)> for Contextual<E, W> {
+  fn from((error, context): (E, W)) -> Contextual<E, W> {
+    Contextual { error, context }
+  }
+}
+
+impl<E, W> From<Contextual<E, W>> for (E, W) {
+  fn from(contextual: Contextual<E, W>) -> (E, W) {
+    contextual.into_tuple()
+  }
+}
+
+impl<E, W: fmt::Display> fmt::Display for Contextual<E, W> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    fmt::Display::fmt(&self.context, f)
+  }
+}
+
+/// Requires the `std` feature. Allows a `Contextual` to be propagated
+/// with `?` into a `Box<dyn Error>`:
+///
+/// ```
+/// use std::{error::Error, fs};
+///
+/// use err_with::ErrWith;
+///
+/// fn read() -> Result<String, Box<dyn Error>> {
+///   Ok(fs::read_to_string("foo/bar").err_with_contextual("reading foo/bar")?)
+/// }
+///
+/// let error = read().unwrap_err();
+/// assert_eq!(error.to_string(), "reading foo/bar");
+/// assert!(error.source().is_some());
+/// ```
+#[cfg(feature = "std")]
+impl<E: Error + 'static, W: fmt::Display + fmt::Debug> Error for Contextual<E, W> {
+  fn source(&self) -> Option<&(dyn Error + 'static)> {
+    Some(&self.error)
+  }
+}
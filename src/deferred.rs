@@ -0,0 +1,30 @@
+use core::fmt;
+
+/// A context which is formatted when displayed, rather than when it is
+/// attached, produced by `err_with!(result, move ...)`.
+///
+/// Wraps a closure which writes the context to a formatter. Since the
+/// closure runs each time the context is displayed, it should only
+/// capture owned values, or `'static` references.
+#[derive(Clone, Copy)]
+pub struct Deferred<F>(F);
+
+impl<F: Fn(&mut fmt::Formatter) -> fmt::Result> Deferred<F> {
+  pub fn new(f: F) -> Deferred<F> {
+    Deferred(f)
+  }
+}
+
+impl<F: Fn(&mut fmt::Formatter) -> fmt::Result> fmt::Display for Deferred<F> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    (self.0)(f)
+  }
+}
+
+impl<F: Fn(&mut fmt::Formatter) -> fmt::Result> fmt::Debug for Deferred<F> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_tuple("Deferred")
+      .field(&format_args!("{}", self))
+      .finish()
+  }
+}
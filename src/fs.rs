@@ -0,0 +1,93 @@
+//! Wrappers around `std::fs` functions which attach the path or paths
+//! that were operated on to errors, as if by `ErrWith::err_with`.
+//! Enabled by the `std` feature.
+//!
+//! ```
+//! use std::path::Path;
+//!
+//! use err_with::fs;
+//!
+//! let (_, path) = fs::read_to_string("foo/bar").unwrap_err();
+//! assert_eq!(path, Path::new("foo/bar"));
+//!
+//! let (_, path) = fs::open("foo/bar").unwrap_err();
+//! assert_eq!(path, Path::new("foo/bar"));
+//!
+//! let (_, (from, to)) = fs::rename("foo/bar", "foo/baz").unwrap_err();
+//! assert_eq!(from, Path::new("foo/bar"));
+//! assert_eq!(to, Path::new("foo/baz"));
+//! ```
+
+use std::{
+  borrow::ToOwned,
+  fs::{self, Metadata, ReadDir},
+  io,
+  path::{Path, PathBuf},
+  string::String,
+  vec::Vec,
+};
+
+use crate::ErrWith;
+
+/// The result of an `fs` operation, with the path or paths operated on
+/// attached to errors.
+pub type Result<T, W = PathBuf> = std::result::Result<T, (io::Error, W)>;
+
+pub fn read<P: AsRef<Path>>(path: P) -> Result<Vec<u8>> {
+  let path = path.as_ref();
+  fs::read(path).err_with_lazy(|| path.to_owned())
+}
+
+pub fn read_to_string<P: AsRef<Path>>(path: P) -> Result<String> {
+  let path = path.as_ref();
+  fs::read_to_string(path).err_with_lazy(|| path.to_owned())
+}
+
+pub fn write<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> Result<()> {
+  let path = path.as_ref();
+  fs::write(path, contents).err_with_lazy(|| path.to_owned())
+}
+
+pub fn create_dir_all<P: AsRef<Path>>(path: P) -> Result<()> {
+  let path = path.as_ref();
+  fs::create_dir_all(path).err_with_lazy(|| path.to_owned())
+}
+
+pub fn remove_file<P: AsRef<Path>>(path: P) -> Result<()> {
+  let path = path.as_ref();
+  fs::remove_file(path).err_with_lazy(|| path.to_owned())
+}
+
+pub fn metadata<P: AsRef<Path>>(path: P) -> Result<Metadata> {
+  let path = path.as_ref();
+  fs::metadata(path).err_with_lazy(|| path.to_owned())
+}
+
+pub fn read_dir<P: AsRef<Path>>(path: P) -> Result<ReadDir> {
+  let path = path.as_ref();
+  fs::read_dir(path).err_with_lazy(|| path.to_owned())
+}
+
+/// Errors have both the source and destination paths attached.
+pub fn rename<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> Result<(), (PathBuf, PathBuf)> {
+  let (from, to) = (from.as_ref(), to.as_ref());
+  fs::rename(from, to).err_with_lazy(|| (from.to_owned(), to.to_owned()))
+}
+
+/// Errors have both the source and destination paths attached.
+pub fn copy<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> Result<u64, (PathBuf, PathBuf)> {
+  let (from, to) = (from.as_ref(), to.as_ref());
+  fs::copy(from, to).err_with_lazy(|| (from.to_owned(), to.to_owned()))
+}
+
+/// Open a file in read-only mode, as with `std::fs::File::open`.
+pub fn open<P: AsRef<Path>>(path: P) -> Result<fs::File> {
+  let path = path.as_ref();
+  fs::File::open(path).err_with_lazy(|| path.to_owned())
+}
+
+/// Create or truncate a file, as with `std::fs::File::create`.
+pub fn create<P: AsRef<Path>>(path: P) -> Result<fs::File> {
+  let path = path.as_ref();
+  fs::File::create(path).err_with_lazy(|| path.to_owned())
+}
@@ -0,0 +1,117 @@
+use core::{
+  future::Future,
+  pin::Pin,
+  task::{Context, Poll},
+};
+
+use pin_project_lite::pin_project;
+
+use crate::ErrWith;
+
+/// Extension trait for futures with output `Result<T, E>`, attaching a
+/// context to the error when the future completes.
+///
+/// This allows contexts to be attached to futures themselves, for use
+/// with combinators like `join` and `select`. It does not depend on any
+/// particular runtime:
+///
+/// ```
+/// use std::{
+///   future::Future,
+///   pin::pin,
+///   task::{Context, Poll, Waker},
+/// };
+///
+/// use err_with::ErrWithFuture;
+///
+/// fn block_on<F: Future>(future: F) -> F::Output {
+///   let mut future = pin!(future);
+///   let mut cx = Context::from_waker(Waker::noop());
+///   loop {
+///     if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+///       return output;
+///     }
+///   }
+/// }
+///
+/// let future = async { Err::<(), _>("failed") };
+/// assert_eq!(block_on(future.err_with("request")), Err(("failed", "request")));
+///
+/// let future = async { Ok::<_, ()>(1) };
+/// assert_eq!(block_on(future.err_with_lazy(|| unreachable!())), Ok::<_, ((), ())>(1));
+/// ```
+pub trait ErrWithFuture: Future + Sized {
+  fn err_with<W>(self, with: W) -> WithFuture<Self, W> {
+    WithFuture {
+      future: self,
+      with: Some(with),
+    }
+  }
+
+  /// Like `err_with`, but only calls `f` if the future completes with
+  /// an error.
+  fn err_with_lazy<F>(self, f: F) -> WithLazyFuture<Self, F> {
+    WithLazyFuture {
+      future: self,
+      f: Some(f),
+    }
+  }
+}
+
+impl<F: Future> ErrWithFuture for F {}
+
+pin_project! {
+  /// Future returned by `ErrWithFuture::err_with`.
+  #[derive(Debug)]
+  #[must_use = "futures do nothing unless polled"]
+  pub struct WithFuture<F, W> {
+    #[pin]
+    future: F,
+    with: Option<W>,
+  }
+}
+
+impl<F, W, T, E> Future for WithFuture<F, W>
+where
+  F: Future<Output = Result<T, E>>,
+{
+  type Output = Result<T, (E, W)>;
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+    let this = self.project();
+    let with = this.with;
+
+    this.future.poll(cx).map(|result| {
+      result.err_with_lazy(|| with.take().expect("`WithFuture` polled after completion"))
+    })
+  }
+}
+
+pin_project! {
+  /// Future returned by `ErrWithFuture::err_with_lazy`.
+  #[derive(Debug)]
+  #[must_use = "futures do nothing unless polled"]
+  pub struct WithLazyFuture<F, G> {
+    #[pin]
+    future: F,
+    f: Option<G>,
+  }
+}
+
+impl<F, G, W, T, E> Future for WithLazyFuture<F, G>
+where
+  F: Future<Output = Result<T, E>>,
+  G: FnOnce() -> W,
+{
+  type Output = Result<T, (E, W)>;
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+    let this = self.project();
+    let f = this.f;
+
+    this.future.poll(cx).map(|result| {
+      let f = f.take().expect("`WithLazyFuture` polled after completion");
+      result.err_with_lazy(f)
+    })
+  }
+}
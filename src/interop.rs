@@ -0,0 +1,133 @@
+use std::{error::Error, fmt::Display};
+
+use crate::ErrWith;
+
+/// Convert a `Result<T, (E, W)>` produced by `err_with` into an
+/// `anyhow::Result<T>`, with `E` as the source and `W` as a context
+/// layer. Enabled by the `anyhow` feature:
+///
+/// ```
+/// use std::fs;
+///
+/// use err_with::{ErrWith, IntoAnyhow};
+///
+/// fn main() -> anyhow::Result<()> {
+///   let error = fs::read("foo/bar").err_with("reading foo/bar").into_anyhow().unwrap_err();
+///   assert_eq!(error.to_string(), "reading foo/bar");
+///   assert!(error.source().is_some());
+///   Ok(())
+/// }
+/// ```
+#[cfg(feature = "anyhow")]
+pub trait IntoAnyhow<T> {
+  fn into_anyhow(self) -> anyhow::Result<T>;
+}
+
+#[cfg(feature = "anyhow")]
+impl<T, E, W> IntoAnyhow<T> for Result<T, (E, W)>
+where
+  E: Error + Send + Sync + 'static,
+  W: Display + Send + Sync + 'static,
+{
+  fn into_anyhow(self) -> anyhow::Result<T> {
+    self.map_err(|(error, with)| anyhow::Error::new(error).context(with))
+  }
+}
+
+/// Extension trait for `ErrWith` implementors, enabled by the `anyhow`
+/// feature:
+///
+/// ```
+/// use std::fs;
+///
+/// use err_with::ErrWithAnyhow;
+///
+/// let error = fs::read("foo/bar").err_with_anyhow("reading foo/bar").unwrap_err();
+/// assert_eq!(error.to_string(), "reading foo/bar");
+/// assert!(error.source().is_some());
+/// ```
+#[cfg(feature = "anyhow")]
+pub trait ErrWithAnyhow<T, E> {
+  /// Attach `with` as a context layer on an `anyhow::Error` with `E` as
+  /// its source. Equivalent to `err_with(with).into_anyhow()`.
+  fn err_with_anyhow<W>(self, with: W) -> anyhow::Result<T>
+  where
+    E: Error + Send + Sync + 'static,
+    W: Display + Send + Sync + 'static;
+}
+
+#[cfg(feature = "anyhow")]
+impl<T, E, R: ErrWith<T, E>> ErrWithAnyhow<T, E> for R {
+  fn err_with_anyhow<W>(self, with: W) -> anyhow::Result<T>
+  where
+    E: Error + Send + Sync + 'static,
+    W: Display + Send + Sync + 'static,
+  {
+    self.err_with(with).into_anyhow()
+  }
+}
+
+/// Convert a `Result<T, (E, W)>` produced by `err_with` into an
+/// `eyre::Result<T>`, with `E` as the source and `W` as a context
+/// layer. Enabled by the `eyre` feature:
+///
+/// ```
+/// use std::fs;
+///
+/// use err_with::{ErrWith, IntoEyre};
+///
+/// fn main() -> eyre::Result<()> {
+///   let error = fs::read("foo/bar").err_with("reading foo/bar").into_eyre().unwrap_err();
+///   assert_eq!(error.to_string(), "reading foo/bar");
+///   assert!(error.source().is_some());
+///   Ok(())
+/// }
+/// ```
+#[cfg(feature = "eyre")]
+pub trait IntoEyre<T> {
+  fn into_eyre(self) -> eyre::Result<T>;
+}
+
+#[cfg(feature = "eyre")]
+impl<T, E, W> IntoEyre<T> for Result<T, (E, W)>
+where
+  E: Error + Send + Sync + 'static,
+  W: Display + Send + Sync + 'static,
+{
+  fn into_eyre(self) -> eyre::Result<T> {
+    self.map_err(|(error, with)| eyre::Report::new(error).wrap_err(with))
+  }
+}
+
+/// Extension trait for `ErrWith` implementors, enabled by the `eyre`
+/// feature:
+///
+/// ```
+/// use std::fs;
+///
+/// use err_with::ErrWithEyre;
+///
+/// let error = fs::read("foo/bar").err_with_eyre("reading foo/bar").unwrap_err();
+/// assert_eq!(error.to_string(), "reading foo/bar");
+/// assert!(error.source().is_some());
+/// ```
+#[cfg(feature = "eyre")]
+pub trait ErrWithEyre<T, E> {
+  /// Attach `with` as a context layer on an `eyre::Report` with `E` as
+  /// its source. Equivalent to `err_with(with).into_eyre()`.
+  fn err_with_eyre<W>(self, with: W) -> eyre::Result<T>
+  where
+    E: Error + Send + Sync + 'static,
+    W: Display + Send + Sync + 'static;
+}
+
+#[cfg(feature = "eyre")]
+impl<T, E, R: ErrWith<T, E>> ErrWithEyre<T, E> for R {
+  fn err_with_eyre<W>(self, with: W) -> eyre::Result<T>
+  where
+    E: Error + Send + Sync + 'static,
+    W: Display + Send + Sync + 'static,
+  {
+    self.err_with(with).into_eyre()
+  }
+}
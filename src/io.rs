@@ -0,0 +1,29 @@
+use std::io;
+
+use crate::{Conditional, ErrWith};
+
+/// Extension trait for `io::Result<T>`, attaching a context only to
+/// errors of a particular `io::ErrorKind`. Enabled by the `std` feature.
+///
+/// ```
+/// use std::{fs, io::ErrorKind};
+///
+/// use err_with::{Conditional, IoErrWith};
+///
+/// match fs::read("foo/bar").err_with_kind(ErrorKind::NotFound, "foo/bar") {
+///   Err(Conditional::With(error, path)) => {
+///     assert_eq!(error.kind(), ErrorKind::NotFound);
+///     assert_eq!(path, "foo/bar");
+///   }
+///   _ => panic!(),
+/// }
+/// ```
+pub trait IoErrWith<T> {
+  fn err_with_kind<W>(self, kind: io::ErrorKind, with: W) -> Result<T, Conditional<io::Error, W>>;
+}
+
+impl<T> IoErrWith<T> for io::Result<T> {
+  fn err_with_kind<W>(self, kind: io::ErrorKind, with: W) -> Result<T, Conditional<io::Error, W>> {
+    self.err_with_if(|error| error.kind() == kind, with)
+  }
+}
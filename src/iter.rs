@@ -0,0 +1,157 @@
+use core::marker::PhantomData;
+
+use crate::ErrWith;
+
+/// Extension trait for iterators over `Result<T, E>`, attaching a
+/// context to every `Err` item with `ErrWith`:
+///
+/// ```
+/// use err_with::ErrWithIter;
+///
+/// let results = vec![Ok(1), Err("bad"), Ok(2), Err("worse")];
+///
+/// assert_eq!(
+///   results.clone().into_iter().err_with("parsing").collect::<Vec<_>>(),
+///   vec![Ok(1), Err(("bad", "parsing")), Ok(2), Err(("worse", "parsing"))],
+/// );
+///
+/// assert_eq!(
+///   results.into_iter().err_with_index(|i| i).collect::<Vec<_>>(),
+///   vec![Ok(1), Err(("bad", 1)), Ok(2), Err(("worse", 3))],
+/// );
+/// ```
+pub trait ErrWithIter: Iterator + Sized {
+  /// Attach a clone of `with` to every error.
+  fn err_with<W: Clone>(self, with: W) -> WithIter<Self, W> {
+    WithIter { iter: self, with }
+  }
+
+  /// Attach the result of calling `f` with the index of each error in
+  /// the iterator, counting both `Ok` and `Err` items.
+  fn err_with_index<F>(self, f: F) -> WithIndexIter<Self, F> {
+    WithIndexIter {
+      iter: self,
+      f,
+      index: 0,
+    }
+  }
+
+  /// Attach a clone of `with` to every error, and convert it into the
+  /// error type `U`, as with `ErrWith::err_into_with`:
+  ///
+  /// ```
+  /// use err_with::ErrWithIter;
+  ///
+  /// #[derive(Debug, PartialEq)]
+  /// struct Error(String);
+  ///
+  /// impl From<(&str, &str)> for Error {
+  ///   fn from((error, with): (&str, &str)) -> Error {
+  ///     Error(format!("{}: {}", with, error))
+  ///   }
+  /// }
+  ///
+  /// let results = vec![Ok(1), Err("bad")]
+  ///   .into_iter()
+  ///   .err_into_with::<Error, _>("parsing")
+  ///   .collect::<Vec<_>>();
+  ///
+  /// assert_eq!(results, vec![Ok(1), Err(Error("parsing: bad".into()))]);
+  /// ```
+  fn err_into_with<U, W: Clone>(self, with: W) -> IntoWithIter<Self, W, U> {
+    IntoWithIter {
+      iter: self,
+      with,
+      target: PhantomData,
+    }
+  }
+}
+
+impl<I: Iterator> ErrWithIter for I {}
+
+/// Iterator returned by `ErrWithIter::err_with`.
+#[derive(Debug, Clone)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct WithIter<I, W> {
+  iter: I,
+  with: W,
+}
+
+impl<I, W, T, E> Iterator for WithIter<I, W>
+where
+  I: Iterator<Item = Result<T, E>>,
+  W: Clone,
+{
+  type Item = Result<T, (E, W)>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let with = &self.with;
+    self
+      .iter
+      .next()
+      .map(|result| result.err_with_lazy(|| with.clone()))
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    self.iter.size_hint()
+  }
+}
+
+/// Iterator returned by `ErrWithIter::err_with_index`.
+#[derive(Debug, Clone)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct WithIndexIter<I, F> {
+  iter: I,
+  f: F,
+  index: usize,
+}
+
+impl<I, F, W, T, E> Iterator for WithIndexIter<I, F>
+where
+  I: Iterator<Item = Result<T, E>>,
+  F: FnMut(usize) -> W,
+{
+  type Item = Result<T, (E, W)>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let result = self.iter.next()?;
+    let index = self.index;
+    self.index += 1;
+    let f = &mut self.f;
+    Some(result.err_with_lazy(|| f(index)))
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    self.iter.size_hint()
+  }
+}
+
+/// Iterator returned by `ErrWithIter::err_into_with`.
+#[derive(Debug, Clone)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct IntoWithIter<I, W, U> {
+  iter: I,
+  with: W,
+  target: PhantomData<fn() -> U>,
+}
+
+impl<I, W, U, T, E> Iterator for IntoWithIter<I, W, U>
+where
+  I: Iterator<Item = Result<T, E>>,
+  W: Clone,
+  U: From<(E, W)>,
+{
+  type Item = Result<T, U>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let with = &self.with;
+    self
+      .iter
+      .next()
+      .map(|result| result.err_with_lazy(|| with.clone()).map_err(U::from))
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    self.iter.size_hint()
+  }
+}
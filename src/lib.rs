@@ -1,7 +1,9 @@
-//! This crate provides a single trait, `ErrWith`, with one method,
+//! This crate is built around a trait, `ErrWith`, whose core method is
 //! `err_with`. `ErrWith` is implemented for `Result<T, E>`, with
 //! `result.err_with(w)` transforming an `Err(e)` to an `Err((e,w))`,
-//! and leaving an `Ok(...)` unchanged.
+//! and leaving an `Ok(...)` unchanged. Its other methods are variations
+//! on `err_with`, attaching contexts lazily, conditionally, or wrapped
+//! in types like `Contextual` and `Located`.
 //!
 //! This is not particularly useful on its own, but can be used to
 //! define conversions from `(E, W)` into your custom error types, so
@@ -39,9 +41,298 @@
 //! }
 //! ```
 //!
+//! `ErrWith` is also implemented for `Option<T>`, with `None` becoming
+//! an `Err((Missing, w))`, so the same conversions can be written for
+//! absent values:
+//!
+//! ```
+//! use err_with::{ErrWith, Missing};
+//!
+//! #[derive(Debug, PartialEq)]
+//! enum Error {
+//!   MissingKey { key: String },
+//! }
+//!
+//! impl From<(Missing, &str)> for Error {
+//!   fn from((_, key): (Missing, &str)) -> Error {
+//!     Error::MissingKey {
+//!       key: key.to_owned(),
+//!     }
+//!   }
+//! }
+//!
+//! fn lookup(key: &str) -> Result<u32, Error> {
+//!   let value = None.err_with(key)?;
+//!   Ok(value)
+//! }
+//!
+//! assert_eq!(
+//!   lookup("foo"),
+//!   Err(Error::MissingKey {
+//!     key: "foo".to_owned()
+//!   })
+//! );
+//! ```
+//!
+//! The crate is `no_std` compatible. The default `std` feature enables
+//! `std::error::Error` impls and other `std`-dependent functionality,
+//! and the `alloc` feature, implied by `std`, enables functionality
+//! that only requires allocation. `ErrWith` itself is always available.
+
+#![no_std]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
 
-pub trait ErrWith<T, E> {
+#[cfg(feature = "std")]
+extern crate std;
+
+pub use crate::{
+  conditional::Conditional,
+  context_result::ContextResultExt,
+  contextual::Contextual,
+  deferred::Deferred,
+  future::ErrWithFuture,
+  iter::ErrWithIter,
+  located::Located,
+  missing::Missing,
+  stack::{Contexts, Push, StackWith},
+  try_with::{try_with, try_with_async},
+};
+
+/// Derive `From<(E, W)>` conversions for error enum variants, enabled
+/// by the `derive` feature.
+///
+/// Each variant annotated with `#[err_with(...)]` must have two fields,
+/// a source and a context, and gets a `From<(Source, Context)>` impl.
+/// The fields can be selected with `source = field` and
+/// `context = field`, and default to the first and second field, as
+/// with a bare `#[err_with]`. A bare `source` selects the field which is
+/// not the context, and a field may not be both. Either field may be
+/// annotated with its type, as in `context = path: PathBuf`, which is
+/// checked against the field's declared type. With
+/// `via = AsRef<T>` or `via = Into<T>`, the impl is generic over any
+/// context that can be converted into the field's type:
+///
+/// ```
+/// use std::{fs, io, path::{Path, PathBuf}};
+///
+/// use err_with::ErrWith;
+///
+/// #[derive(Debug, ErrWith)]
+/// enum Error {
+///   #[err_with(source, context = path: PathBuf, via = AsRef<Path>)]
+///   Io { path: PathBuf, io_error: io::Error },
+///   #[err_with(source = 1, context = 0, via = Into<String>)]
+///   Parse(String, std::num::ParseIntError),
+/// }
+///
+/// fn run() -> Result<u64, Error> {
+///   let text = fs::read_to_string("foo/bar").err_with("foo/bar")?;
+///   Ok(text.parse().err_with("foo/bar")?)
+/// }
+///
+/// match run().unwrap_err() {
+///   Error::Io { path, .. } => assert_eq!(path, Path::new("foo/bar")),
+///   Error::Parse(..) => panic!(),
+/// }
+/// ```
+#[cfg(feature = "derive")]
+pub use err_with_derive::ErrWith;
+
+#[cfg(feature = "stream")]
+pub use crate::stream::ErrWithStream;
+
+#[cfg(feature = "backtrace")]
+pub use crate::traced::{ErrWithBacktrace, Traced};
+
+#[cfg(feature = "std")]
+pub use crate::any::{find_context, AnyContextual, ErrWithAny, FindContext};
+
+#[cfg(feature = "std")]
+pub use crate::io::IoErrWith;
+
+#[cfg(feature = "std")]
+pub use crate::scope::{scope, ErrWithScope, InContext, Scopes};
+
+#[cfg(feature = "anyhow")]
+pub use crate::interop::{ErrWithAnyhow, IntoAnyhow};
+
+#[cfg(feature = "eyre")]
+pub use crate::interop::{ErrWithEyre, IntoEyre};
+
+#[cfg(feature = "serde")]
+pub use crate::snapshot::{LocationSnapshot, RemoteError, RemoteSource, Snapshot};
+
+#[macro_use]
+mod macros;
+
+#[cfg(feature = "std")]
+mod any;
+mod conditional;
+mod context_result;
+mod contextual;
+mod deferred;
+#[cfg(feature = "std")]
+pub mod fs;
+pub mod future;
+#[cfg(any(feature = "anyhow", feature = "eyre"))]
+mod interop;
+#[cfg(feature = "std")]
+mod io;
+pub mod iter;
+mod located;
+mod missing;
+#[cfg(feature = "std")]
+pub mod process;
+#[cfg(feature = "std")]
+pub mod report;
+#[cfg(feature = "std")]
+pub mod scope;
+#[cfg(feature = "serde")]
+mod snapshot;
+mod stack;
+#[cfg(feature = "stream")]
+pub mod stream;
+#[cfg(feature = "backtrace")]
+mod traced;
+mod try_with;
+
+#[doc(hidden)]
+pub mod __private {
+  #[cfg(feature = "alloc")]
+  pub use alloc::format;
+}
+
+/// Attach contexts to errors. Only `err_with`, `err_with_lazy`, and
+/// `err_with_error` are required, the other methods are provided in
+/// terms of them:
+///
+/// ```
+/// use err_with::ErrWith;
+///
+/// struct Outcome(Result<u32, &'static str>);
+///
+/// impl ErrWith<u32, &'static str> for Outcome {
+///   fn err_with<W>(self, with: W) -> Result<u32, (&'static str, W)> {
+///     self.0.err_with(with)
+///   }
+///
+///   fn err_with_lazy<W, F: FnOnce() -> W>(self, f: F) -> Result<u32, (&'static str, W)> {
+///     self.0.err_with_lazy(f)
+///   }
+///
+///   fn err_with_error<W, F: FnOnce(&&'static str) -> W>(
+///     self,
+///     f: F,
+///   ) -> Result<u32, (&'static str, W)> {
+///     self.0.err_with_error(f)
+///   }
+/// }
+///
+/// let error = Outcome(Err("bad")).err_with_contextual("context").unwrap_err();
+/// assert_eq!(error.context, "context");
+/// ```
+pub trait ErrWith<T, E>: Sized {
   fn err_with<W>(self, with: W) -> Result<T, (E, W)>;
+
+  /// Like `err_with`, but only calls `f` to produce the context if
+  /// `self` is an error, so no work is done on the `Ok` path:
+  ///
+  /// ```
+  /// use std::{fs, path::Path};
+  ///
+  /// use err_with::ErrWith;
+  ///
+  /// let path = Path::new("foo/bar");
+  /// let error = fs::read(path).err_with_lazy(|| path.to_owned()).unwrap_err();
+  /// assert_eq!(error.1, path);
+  /// ```
+  fn err_with_lazy<W, F: FnOnce() -> W>(self, f: F) -> Result<T, (E, W)>;
+
+  /// Like `err_with_lazy`, but passes the error to `f`, so the context
+  /// can depend on it:
+  ///
+  /// ```
+  /// use err_with::ErrWith;
+  ///
+  /// let result: Result<(), &str> = Err("bad");
+  /// assert_eq!(result.err_with_error(|e| e.len()), Err(("bad", 3)));
+  /// ```
+  fn err_with_error<W, F: FnOnce(&E) -> W>(self, f: F) -> Result<T, (E, W)>;
+
+  /// Like `err_with`, but produces a `Contextual` instead of a tuple.
+  fn err_with_contextual<W>(self, with: W) -> Result<T, Contextual<E, W>> {
+    self.err_with(with).map_err(Contextual::from)
+  }
+
+  /// Like `err_with`, but records the location of the call in a
+  /// `Located` wrapping the context.
+  #[track_caller]
+  fn err_here<W>(self, with: W) -> Result<T, (E, Located<W>)> {
+    self.err_with(Located::new(with))
+  }
+
+  /// Like `err_with`, but immediately converts `(E, W)` into the error
+  /// type `U`, which can be given with a turbofish, for use where `?`
+  /// is not available to perform the conversion, or the target type
+  /// cannot be inferred:
+  ///
+  /// ```
+  /// use std::{fs, io, path::{Path, PathBuf}};
+  ///
+  /// use err_with::ErrWith;
+  ///
+  /// #[derive(Debug)]
+  /// struct Error {
+  ///   path: PathBuf,
+  /// }
+  ///
+  /// impl<P: AsRef<Path>> From<(io::Error, P)> for Error {
+  ///   fn from((_, path): (io::Error, P)) -> Error {
+  ///     Error {
+  ///       path: path.as_ref().to_owned(),
+  ///     }
+  ///   }
+  /// }
+  ///
+  /// let results = ["foo", "bar"]
+  ///   .iter()
+  ///   .map(|path| fs::read(path).err_into_with::<Error, _>(path))
+  ///   .collect::<Vec<_>>();
+  ///
+  /// assert_eq!(results[1].as_ref().unwrap_err().path, Path::new("bar"));
+  /// ```
+  fn err_into_with<U: From<(E, W)>, W>(self, with: W) -> Result<T, U> {
+    self.err_with(with).map_err(U::from)
+  }
+
+  /// Attach `with` only if `predicate` returns true for the error,
+  /// producing a `Conditional` so that errors without a context can
+  /// still be matched directly:
+  ///
+  /// ```
+  /// use err_with::{Conditional, ErrWith};
+  ///
+  /// let result: Result<(), u32> = Err(1);
+  /// assert_eq!(result.err_with_if(|e| *e > 1, "big"), Err(Conditional::Plain(1)));
+  ///
+  /// let result: Result<(), u32> = Err(2);
+  /// assert_eq!(result.err_with_if(|e| *e > 1, "big"), Err(Conditional::With(2, "big")));
+  /// ```
+  fn err_with_if<W, P: FnOnce(&E) -> bool>(
+    self,
+    predicate: P,
+    with: W,
+  ) -> Result<T, Conditional<E, W>> {
+    self.err_with_error(predicate).map_err(|(error, matched)| {
+      if matched {
+        Conditional::With(error, with)
+      } else {
+        Conditional::Plain(error)
+      }
+    })
+  }
 }
 
 impl<T, E> ErrWith<T, E> for Result<T, E> {
@@ -51,4 +342,35 @@ impl<T, E> ErrWith<T, E> for Result<T, E> {
       Err(error) => Err((error, with)),
     }
   }
+
+  fn err_with_lazy<W, F: FnOnce() -> W>(self, f: F) -> Result<T, (E, W)> {
+    match self {
+      Ok(ok) => Ok(ok),
+      Err(error) => Err((error, f())),
+    }
+  }
+
+  fn err_with_error<W, F: FnOnce(&E) -> W>(self, f: F) -> Result<T, (E, W)> {
+    match self {
+      Ok(ok) => Ok(ok),
+      Err(error) => {
+        let with = f(&error);
+        Err((error, with))
+      }
+    }
+  }
+}
+
+impl<T> ErrWith<T, Missing> for Option<T> {
+  fn err_with<W>(self, with: W) -> Result<T, (Missing, W)> {
+    self.ok_or(Missing).err_with(with)
+  }
+
+  fn err_with_lazy<W, F: FnOnce() -> W>(self, f: F) -> Result<T, (Missing, W)> {
+    self.ok_or(Missing).err_with_lazy(f)
+  }
+
+  fn err_with_error<W, F: FnOnce(&Missing) -> W>(self, f: F) -> Result<T, (Missing, W)> {
+    self.ok_or(Missing).err_with_error(f)
+  }
 }
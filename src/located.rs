@@ -0,0 +1,74 @@
+use core::{fmt, panic::Location};
+
+/// A context annotated with the source location at which it was
+/// attached, produced by `ErrWith::err_here`:
+///
+/// ```
+/// use err_with::ErrWith;
+///
+/// let (result, line) = (Err::<(), _>("error").err_here("context"), line!());
+/// let (_, located) = result.unwrap_err();
+///
+/// assert_eq!(located.context(), &"context");
+/// assert_eq!(located.file(), file!());
+/// assert_eq!(located.line(), line);
+/// assert_eq!(
+///   located.to_string(),
+///   format!("context at {}:{}:{}", file!(), line, located.column()),
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Located<W> {
+  context: W,
+  location: &'static Location<'static>,
+}
+
+impl<W> Located<W> {
+  /// Annotate `context` with the location of the caller.
+  #[track_caller]
+  pub fn new(context: W) -> Located<W> {
+    Located {
+      context,
+      location: Location::caller(),
+    }
+  }
+
+  pub fn context(&self) -> &W {
+    &self.context
+  }
+
+  pub fn into_context(self) -> W {
+    self.context
+  }
+
+  pub fn location(&self) -> &'static Location<'static> {
+    self.location
+  }
+
+  pub fn file(&self) -> &'static str {
+    self.location.file()
+  }
+
+  pub fn line(&self) -> u32 {
+    self.location.line()
+  }
+
+  pub fn column(&self) -> u32 {
+    self.location.column()
+  }
+}
+
+/// Renders as `{context} at {location}`, except in a `Report`, which
+/// renders the location in a separate section.
+impl<W: fmt::Display> fmt::Display for Located<W> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    #[cfg(feature = "std")]
+    {
+      if crate::report::record_location(self.location) {
+        return write!(f, "{}", self.context);
+      }
+    }
+
+    write!(f, "{} at {}", self.context, self.location)
+  }
+}
@@ -0,0 +1,137 @@
+/// Attach a formatted context to a `Result` or `Option`, only
+/// formatting it if it is an error.
+///
+/// `err_with!(result, "format string", args...)` is equivalent to
+/// `result.err_with_lazy(|| format!("format string", args...))`, and
+/// requires the `alloc` feature:
+///
+/// ```
+/// # #[cfg(feature = "alloc")]
+/// # {
+/// use err_with::err_with;
+///
+/// let (path, user) = ("foo/bar", "root");
+/// let result: Result<(), &str> = Err("denied");
+///
+/// assert_eq!(
+///   err_with!(result, "reading {} for {}", path, user),
+///   Err(("denied", "reading foo/bar for root".to_owned())),
+/// );
+/// # }
+/// ```
+///
+/// With `move` before the format string, the arguments are moved into
+/// a `Deferred`, and not formatted until the context is displayed:
+///
+/// ```
+/// use err_with::err_with;
+///
+/// let path = "foo/bar";
+/// let (_, context) = err_with!(Err::<(), _>("denied"), move "reading {}", path).unwrap_err();
+///
+/// assert_eq!(context.to_string(), "reading foo/bar");
+/// ```
+#[macro_export]
+macro_rules! err_with {
+  ($result:expr, move $($format:tt)+) => {
+    $crate::ErrWith::err_with_lazy($result, move || {
+      $crate::Deferred::new(move |f: &mut ::core::fmt::Formatter| ::core::write!(f, $($format)+))
+    })
+  };
+  ($result:expr, $($format:tt)+) => {
+    $crate::ErrWith::err_with_lazy($result, || $crate::__private::format!($($format)+))
+  };
+}
+
+/// Return early with the error `(error, with)`, converted with `From`
+/// into the function's error type, as if it had been produced by
+/// `err_with` and propagated with `?`:
+///
+/// ```
+/// use err_with::bail_with;
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Error {
+///   Negative { value: i64 },
+/// }
+///
+/// struct NegativeError;
+///
+/// impl From<(NegativeError, i64)> for Error {
+///   fn from((_, value): (NegativeError, i64)) -> Error {
+///     Error::Negative { value }
+///   }
+/// }
+///
+/// fn check(value: i64) -> Result<i64, Error> {
+///   if value < 0 {
+///     bail_with!(NegativeError, value);
+///   }
+///   Ok(value)
+/// }
+///
+/// assert_eq!(check(-1), Err(Error::Negative { value: -1 }));
+/// ```
+#[macro_export]
+macro_rules! bail_with {
+  ($error:expr, $with:expr $(,)?) => {
+    return ::core::result::Result::Err(::core::convert::From::from(($error, $with)))
+  };
+}
+
+/// Return early with the error `(error, with)` if `condition` is
+/// false, as with `bail_with!`:
+///
+/// ```
+/// use err_with::ensure_with;
+///
+/// fn check(value: i64) -> Result<i64, (&'static str, i64)> {
+///   ensure_with!(value >= 0, "negative", value);
+///   Ok(value)
+/// }
+///
+/// assert_eq!(check(1), Ok(1));
+/// assert_eq!(check(-1), Err(("negative", -1)));
+/// ```
+#[macro_export]
+macro_rules! ensure_with {
+  ($condition:expr, $error:expr, $with:expr $(,)?) => {
+    if !$condition {
+      $crate::bail_with!($error, $with);
+    }
+  };
+}
+
+/// Run a block, attaching a context to any error it returns, using
+/// `try_with`, or, for `async` blocks, `try_with_async`:
+///
+/// ```
+/// use std::{fs, io};
+///
+/// use err_with::try_with;
+///
+/// let result = try_with!("loading config", {
+///   let path = fs::read_to_string("foo/path")?;
+///   Ok::<_, io::Error>(path)
+/// });
+///
+/// assert_eq!(result.unwrap_err().1, "loading config");
+///
+/// let future = try_with!("loading config", async move {
+///   Err::<(), _>("failed")
+/// });
+///
+/// assert_eq!(
+///   futures::executor::block_on(future),
+///   Err(("failed", "loading config")),
+/// );
+/// ```
+#[macro_export]
+macro_rules! try_with {
+  ($with:expr, async $($body:tt)+) => {
+    $crate::try_with_async($with, async $($body)+)
+  };
+  ($with:expr, $body:block) => {
+    $crate::try_with($with, || $body)
+  };
+}
@@ -0,0 +1,17 @@
+use core::fmt;
+
+#[cfg(feature = "std")]
+use std::error::Error;
+
+/// The error produced when calling `ErrWith` methods on a `None`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Missing;
+
+impl fmt::Display for Missing {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "missing value")
+  }
+}
+
+#[cfg(feature = "std")]
+impl Error for Missing {}
@@ -0,0 +1,251 @@
+//! An extension trait for `std::process::Command` which attaches the
+//! command line to errors, and treats unsuccessful exit statuses as
+//! errors. Enabled by the `std` feature.
+//!
+//! ```
+//! # #[cfg(unix)]
+//! # {
+//! use std::process::Command;
+//!
+//! use err_with::process::{CommandExt, Failure};
+//!
+//! assert!(Command::new("true").checked_status().is_ok());
+//!
+//! let (failure, command) = Command::new("false").checked_status().unwrap_err();
+//! assert_eq!(command.to_string(), "false");
+//! assert_eq!(failure.to_string(), "exited with code 1");
+//!
+//! let (failure, _) = Command::new("sh")
+//!   .args(["-c", "kill -9 $$"])
+//!   .checked_status()
+//!   .unwrap_err();
+//! assert_eq!(failure.to_string(), "terminated by signal 9");
+//!
+//! let (failure, command) = Command::new("sh")
+//!   .args(["-c", "echo oops >&2; exit 3"])
+//!   .checked_output()
+//!   .unwrap_err();
+//!
+//! assert_eq!(command.to_string(), "sh -c 'echo oops >&2; exit 3'");
+//!
+//! match failure {
+//!   Failure::Status(error) => {
+//!     assert_eq!(error.code(), Some(3));
+//!     assert_eq!(error.stderr_tail(), Some("oops\n"));
+//!     assert_eq!(error.to_string(), "exited with code 3: oops");
+//!   }
+//!   Failure::Io(_) => panic!(),
+//! }
+//! # }
+//! ```
+
+use std::{
+  borrow::ToOwned,
+  error::Error,
+  ffi::{OsStr, OsString},
+  fmt, io,
+  process::{Command, ExitStatus, Output},
+  string::String,
+  vec::Vec,
+};
+
+use crate::ErrWith;
+
+/// The maximum number of bytes of captured standard error retained in
+/// a `StatusError`.
+pub const STDERR_TAIL_LEN: usize = 4096;
+
+/// Extension trait for `Command`.
+pub trait CommandExt {
+  /// Like `Command::status`, but attaches the command line to errors,
+  /// and returns an error if the command does not exit successfully.
+  fn checked_status(&mut self) -> Result<ExitStatus, (Failure, CommandLine)>;
+
+  /// Like `Command::output`, but attaches the command line to errors,
+  /// and returns an error, including the tail of the command's standard
+  /// error, if the command does not exit successfully.
+  fn checked_output(&mut self) -> Result<Output, (Failure, CommandLine)>;
+}
+
+impl CommandExt for Command {
+  fn checked_status(&mut self) -> Result<ExitStatus, (Failure, CommandLine)> {
+    let status = self
+      .status()
+      .map_err(Failure::Io)
+      .err_with_lazy(|| CommandLine::new(self))?;
+
+    if !status.success() {
+      return Err(Failure::Status(StatusError::new(status, None))).err_with(CommandLine::new(self));
+    }
+
+    Ok(status)
+  }
+
+  fn checked_output(&mut self) -> Result<Output, (Failure, CommandLine)> {
+    let output = self
+      .output()
+      .map_err(Failure::Io)
+      .err_with_lazy(|| CommandLine::new(self))?;
+
+    if !output.status.success() {
+      return Err(Failure::Status(StatusError::new(
+        output.status,
+        Some(&output.stderr),
+      )))
+      .err_with(CommandLine::new(self));
+    }
+
+    Ok(output)
+  }
+}
+
+/// The program and arguments of a `Command`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CommandLine {
+  program: OsString,
+  args: Vec<OsString>,
+}
+
+impl CommandLine {
+  pub fn new(command: &Command) -> CommandLine {
+    CommandLine {
+      program: command.get_program().to_owned(),
+      args: command.get_args().map(ToOwned::to_owned).collect(),
+    }
+  }
+
+  pub fn program(&self) -> &OsStr {
+    &self.program
+  }
+
+  pub fn args(&self) -> impl Iterator<Item = &OsStr> {
+    self.args.iter().map(OsString::as_os_str)
+  }
+}
+
+/// Renders the command line, single-quoting words which contain
+/// whitespace or quotes, or are empty.
+impl fmt::Display for CommandLine {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    for (i, word) in std::iter::once(self.program())
+      .chain(self.args())
+      .enumerate()
+    {
+      if i > 0 {
+        write!(f, " ")?;
+      }
+
+      let word = word.to_string_lossy();
+
+      if word.is_empty()
+        || word
+          .chars()
+          .any(|c| c.is_whitespace() || c == '\'' || c == '"')
+      {
+        write!(f, "'{}'", word.replace('\'', r"'\''"))?;
+      } else {
+        write!(f, "{}", word)?;
+      }
+    }
+
+    Ok(())
+  }
+}
+
+/// The ways in which running a command can fail.
+#[derive(Debug)]
+pub enum Failure {
+  /// The command could not be run.
+  Io(io::Error),
+  /// The command did not exit successfully.
+  Status(StatusError),
+}
+
+impl fmt::Display for Failure {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Failure::Io(io_error) => write!(f, "{}", io_error),
+      Failure::Status(status_error) => write!(f, "{}", status_error),
+    }
+  }
+}
+
+impl Error for Failure {
+  fn source(&self) -> Option<&(dyn Error + 'static)> {
+    match self {
+      Failure::Io(io_error) => Some(io_error),
+      Failure::Status(_) => None,
+    }
+  }
+}
+
+/// A command exited unsuccessfully.
+#[derive(Debug, Clone)]
+pub struct StatusError {
+  status: ExitStatus,
+  stderr_tail: Option<String>,
+}
+
+impl StatusError {
+  fn new(status: ExitStatus, stderr: Option<&[u8]>) -> StatusError {
+    StatusError {
+      status,
+      stderr_tail: stderr.map(|stderr| {
+        let start = stderr.len().saturating_sub(STDERR_TAIL_LEN);
+        String::from_utf8_lossy(&stderr[start..]).into_owned()
+      }),
+    }
+  }
+
+  pub fn status(&self) -> ExitStatus {
+    self.status
+  }
+
+  /// The exit code of the command, if it exited normally.
+  pub fn code(&self) -> Option<i32> {
+    self.status.code()
+  }
+
+  /// The signal that terminated the command, if any.
+  #[cfg(unix)]
+  pub fn signal(&self) -> Option<i32> {
+    std::os::unix::process::ExitStatusExt::signal(&self.status)
+  }
+
+  /// The last `STDERR_TAIL_LEN` bytes of the command's standard error,
+  /// if it was captured.
+  pub fn stderr_tail(&self) -> Option<&str> {
+    self.stderr_tail.as_deref()
+  }
+}
+
+impl fmt::Display for StatusError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self.code() {
+      Some(code) => write!(f, "exited with code {}", code)?,
+      None => {
+        #[cfg(unix)]
+        {
+          if let Some(signal) = self.signal() {
+            write!(f, "terminated by signal {}", signal)?;
+          } else {
+            write!(f, "exited with {}", self.status)?;
+          }
+        }
+        #[cfg(not(unix))]
+        write!(f, "exited with {}", self.status)?;
+      }
+    }
+
+    if let Some(tail) = self.stderr_tail() {
+      let tail = tail.trim();
+      if !tail.is_empty() {
+        write!(f, ": {}", tail)?;
+      }
+    }
+
+    Ok(())
+  }
+}
+
+impl Error for StatusError {}
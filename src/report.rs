@@ -0,0 +1,511 @@
+//! Render errors and their source chains, including the contexts
+//! attached by `ErrWith`, in a consistent, human-readable format.
+//! Enabled by the `std` feature.
+//!
+//! ```
+//! use std::io;
+//!
+//! use err_with::{report::Report, Contextual};
+//!
+//! let error = Contextual::new(
+//!   Contextual::new(io::Error::new(io::ErrorKind::NotFound, "file not found"), "foo/bar"),
+//!   "loading configuration from the default location",
+//! );
+//!
+//! assert_eq!(
+//!   Report::new(&error).width(40).to_string(),
+//!   "\
+//! error: loading configuration from the
+//!        default location
+//! caused by:
+//!   1: foo/bar
+//!   2: file not found",
+//! );
+//! ```
+
+use std::{
+  boxed::Box,
+  cell::RefCell,
+  env, eprintln,
+  error::Error,
+  fmt, format,
+  io::{self, ErrorKind, IsTerminal},
+  panic::Location,
+  process::{ExitCode, Termination},
+  string::String,
+  thread_local,
+  vec::Vec,
+};
+
+use crate::Contextual;
+
+const BOLD_RED: &str = "\x1b[1;31m";
+const BOLD_YELLOW: &str = "\x1b[1;33m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+/// A renderer for an error and its source chain, displaying the error
+/// followed by a numbered list of its causes.
+///
+/// The locations of `Located` contexts, as attached by
+/// `ErrWith::err_here`, are rendered in `location:` sections beneath
+/// their messages:
+///
+/// ```
+/// use err_with::{report::Report, Contextual, ErrWith};
+///
+/// let (result, line) = (None::<()>.err_here("foo/bar"), line!());
+/// let error = Contextual::from(result.unwrap_err());
+///
+/// assert_eq!(
+///   Report::new(&error).to_string(),
+///   format!(
+///     "\
+/// error: foo/bar
+///        location: {}:{}:{}
+/// caused by:
+///   1: missing value",
+///     file!(),
+///     line,
+///     error.context.column(),
+///   ),
+/// );
+/// ```
+///
+/// With `details`, each error is rendered in the alternate form, `{:#}`,
+/// with any additional lines indented beneath its message, and the
+/// backtraces of `Traced` contexts, as attached by
+/// `ErrWithBacktrace::err_with_backtrace`, are rendered in `backtrace:`
+/// sections after the source chain.
+#[derive(Clone, Copy)]
+pub struct Report<'a> {
+  error: &'a (dyn Error + 'static),
+  width: Option<usize>,
+  color: bool,
+  details: bool,
+}
+
+impl<'a> Report<'a> {
+  /// A report without wrapping, colors, or details.
+  pub fn new(error: &'a (dyn Error + 'static)) -> Report<'a> {
+    Report {
+      error,
+      width: None,
+      color: false,
+      details: false,
+    }
+  }
+
+  /// A report for printing to standard error, with colors if standard
+  /// error is a terminal and `NO_COLOR` is not set, wrapping to the
+  /// width in `COLUMNS`, if set, or otherwise to the width of the
+  /// terminal, and details if `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE`
+  /// are set to something other than `0`.
+  pub fn stderr(error: &'a (dyn Error + 'static)) -> Report<'a> {
+    let color = !matches!(env::var_os("NO_COLOR"), Some(value) if !value.is_empty())
+      && io::stderr().is_terminal();
+
+    let width = env::var("COLUMNS")
+      .ok()
+      .and_then(|columns| columns.parse().ok())
+      .or_else(|| {
+        terminal_size::terminal_size_of(io::stderr()).map(|(width, _)| usize::from(width.0))
+      });
+
+    let details = ["RUST_LIB_BACKTRACE", "RUST_BACKTRACE"]
+      .iter()
+      .filter_map(env::var_os)
+      .any(|value| value != "0");
+
+    Report {
+      error,
+      width,
+      color,
+      details,
+    }
+  }
+
+  /// Wrap lines to `width` columns.
+  pub fn width(self, width: usize) -> Report<'a> {
+    Report {
+      width: Some(width),
+      ..self
+    }
+  }
+
+  /// Use ANSI escape codes to color the output.
+  pub fn color(self, color: bool) -> Report<'a> {
+    Report { color, ..self }
+  }
+
+  /// Render each error in the alternate form.
+  pub fn details(self, details: bool) -> Report<'a> {
+    Report { details, ..self }
+  }
+
+  fn write_label(&self, f: &mut fmt::Formatter, label: &str, style: &str) -> fmt::Result {
+    if self.color {
+      write!(f, "{}{}{}", style, label, RESET)
+    } else {
+      write!(f, "{}", label)
+    }
+  }
+
+  /// Render `error`, capturing the sections recorded by its context.
+  fn render(&self, error: &dyn Error) -> (String, Sections) {
+    let previous = SECTIONS.with(|sections| sections.replace(Some(Sections::default())));
+
+    let message = if self.details {
+      format!("{:#}", error)
+    } else {
+      format!("{}", error)
+    };
+
+    let sections = SECTIONS.with(|sections| sections.replace(previous));
+
+    (message, sections.unwrap_or_default())
+  }
+
+  fn write_error(
+    &self,
+    f: &mut fmt::Formatter,
+    error: &dyn Error,
+    indent: usize,
+    backtraces: &mut Vec<String>,
+  ) -> fmt::Result {
+    let (message, sections) = self.render(error);
+
+    self.write_message(f, &message, indent)?;
+
+    if let Some(location) = sections.location {
+      writeln!(f)?;
+      write!(f, "{:indent$}", "", indent = indent)?;
+      self.write_label(f, "location:", DIM)?;
+      write!(f, " {}", location)?;
+    }
+
+    backtraces.extend(sections.backtrace);
+
+    Ok(())
+  }
+
+  fn write_message(&self, f: &mut fmt::Formatter, message: &str, indent: usize) -> fmt::Result {
+    let width = self
+      .width
+      .map(|width| width.saturating_sub(indent).max(1))
+      .unwrap_or(usize::MAX);
+
+    let mut first = true;
+
+    for line in message.lines() {
+      for wrapped in wrap(line, width) {
+        let wrapped = wrapped.trim_end();
+        if !first {
+          writeln!(f)?;
+          if !wrapped.is_empty() {
+            write!(f, "{:indent$}", "", indent = indent)?;
+          }
+        }
+        write!(f, "{}", wrapped)?;
+        first = false;
+      }
+    }
+
+    Ok(())
+  }
+}
+
+impl fmt::Display for Report<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let mut backtraces = Vec::new();
+
+    self.write_label(f, "error:", BOLD_RED)?;
+    write!(f, " ")?;
+    self.write_error(f, self.error, "error: ".len(), &mut backtraces)?;
+
+    let mut source = self.error.source();
+
+    if source.is_some() {
+      writeln!(f)?;
+      self.write_label(f, "caused by:", BOLD_YELLOW)?;
+    }
+
+    let mut i = 1;
+    while let Some(error) = source {
+      let label = format!("{:>3}:", i);
+      writeln!(f)?;
+      self.write_label(f, &label, DIM)?;
+      write!(f, " ")?;
+      self.write_error(f, error, label.len() + 1, &mut backtraces)?;
+      source = error.source();
+      i += 1;
+    }
+
+    for backtrace in backtraces {
+      writeln!(f)?;
+      self.write_label(f, "backtrace:", BOLD_YELLOW)?;
+      for line in backtrace.trim_end().lines() {
+        writeln!(f)?;
+        write!(f, "{}", line.trim_end())?;
+      }
+    }
+
+    Ok(())
+  }
+}
+
+impl fmt::Debug for Report<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    fmt::Display::fmt(self, f)
+  }
+}
+
+/// Sections recorded by the context of an error while it is rendered by
+/// a `Report`.
+#[derive(Default)]
+struct Sections {
+  location: Option<&'static Location<'static>>,
+  backtrace: Option<String>,
+}
+
+thread_local! {
+  static SECTIONS: RefCell<Option<Sections>> = const { RefCell::new(None) };
+}
+
+/// Record `location` in a section, returning false if no `Report` is
+/// being rendered, in which case the caller should render it inline.
+pub(crate) fn record_location(location: &'static Location<'static>) -> bool {
+  SECTIONS.with(|sections| match sections.borrow_mut().as_mut() {
+    Some(sections) => {
+      sections.location = Some(location);
+      true
+    }
+    None => false,
+  })
+}
+
+/// Record `backtrace` in a section, as with `record_location`.
+#[cfg(feature = "backtrace")]
+pub(crate) fn record_backtrace(backtrace: &std::backtrace::Backtrace) -> bool {
+  SECTIONS.with(|sections| match sections.borrow_mut().as_mut() {
+    Some(sections) => {
+      sections.backtrace = Some(format!("{}", backtrace));
+      true
+    }
+    None => false,
+  })
+}
+
+/// A wrapper for the result of `main`, which, on error, prints a
+/// `Report` of the error to standard error and exits with the code
+/// given by `ErrorExitCode`:
+///
+/// ```
+/// use std::{
+///   fs, io,
+///   process::{ExitCode, Termination},
+/// };
+///
+/// use err_with::{report::ExitReport, Contextual, ErrWith};
+///
+/// fn run() -> Result<(), Contextual<io::Error, &'static str>> {
+///   fs::read("foo/bar").err_with_contextual("foo/bar")?;
+///   Ok(())
+/// }
+///
+/// // When returned from `main`, prints a report and exits with code
+/// // 66, `EX_NOINPUT`, if `foo/bar` does not exist.
+/// fn try_main() -> ExitReport<Contextual<io::Error, &'static str>> {
+///   run().into()
+/// }
+///
+/// assert_eq!(try_main().report(), ExitCode::from(66));
+/// ```
+#[derive(Debug)]
+pub struct ExitReport<E>(pub Result<(), E>);
+
+impl<E> From<Result<(), E>> for ExitReport<E> {
+  fn from(result: Result<(), E>) -> ExitReport<E> {
+    ExitReport(result)
+  }
+}
+
+impl<E: Error + ErrorExitCode + 'static> Termination for ExitReport<E> {
+  fn report(self) -> ExitCode {
+    match self.0 {
+      Ok(()) => ExitCode::SUCCESS,
+      Err(error) => {
+        eprintln!("{}", Report::stderr(&error));
+        ExitCode::from(error.exit_code())
+      }
+    }
+  }
+}
+
+/// Boxed errors, which do not implement `ErrorExitCode`, exit with the
+/// code given by `exit_code`:
+///
+/// ```
+/// use std::{
+///   error::Error,
+///   fs,
+///   process::{ExitCode, Termination},
+/// };
+///
+/// use err_with::{report::ExitReport, ErrWith};
+///
+/// fn run() -> Result<(), Box<dyn Error>> {
+///   fs::read("foo/bar").err_with_contextual("foo/bar")?;
+///   Ok(())
+/// }
+///
+/// fn try_main() -> ExitReport<Box<dyn Error>> {
+///   run().into()
+/// }
+///
+/// assert_eq!(try_main().report(), ExitCode::from(66));
+/// ```
+impl Termination for ExitReport<Box<dyn Error>> {
+  fn report(self) -> ExitCode {
+    match self.0 {
+      Ok(()) => ExitCode::SUCCESS,
+      Err(error) => {
+        eprintln!("{}", Report::stderr(error.as_ref()));
+        ExitCode::from(exit_code(error.as_ref()))
+      }
+    }
+  }
+}
+
+impl Termination for ExitReport<Box<dyn Error + Send + Sync>> {
+  fn report(self) -> ExitCode {
+    match self.0 {
+      Ok(()) => ExitCode::SUCCESS,
+      Err(error) => {
+        eprintln!("{}", Report::stderr(error.as_ref()));
+        ExitCode::from(exit_code(error.as_ref()))
+      }
+    }
+  }
+}
+
+/// The exit code used by `ExitReport` for an error. The default
+/// implementation uses `exit_code`, so an empty impl is sufficient
+/// for types which do not need a custom exit code:
+///
+/// ```
+/// use std::{fmt, io};
+///
+/// use err_with::{report::ErrorExitCode, Contextual};
+///
+/// #[derive(Debug)]
+/// struct Usage;
+///
+/// impl fmt::Display for Usage {
+///   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+///     write!(f, "invalid usage")
+///   }
+/// }
+///
+/// impl std::error::Error for Usage {}
+///
+/// impl ErrorExitCode for Usage {
+///   fn exit_code(&self) -> u8 {
+///     64
+///   }
+/// }
+///
+/// let error = io::Error::new(io::ErrorKind::PermissionDenied, "denied");
+/// assert_eq!(Contextual::new(error, "foo/bar").exit_code(), 77);
+/// assert_eq!(Usage.exit_code(), 64);
+/// ```
+pub trait ErrorExitCode {
+  fn exit_code(&self) -> u8
+  where
+    Self: Error + Sized + 'static,
+  {
+    exit_code(self)
+  }
+}
+
+impl ErrorExitCode for io::Error {}
+
+impl ErrorExitCode for crate::AnyContextual {}
+
+impl ErrorExitCode for crate::Missing {}
+
+impl ErrorExitCode for crate::process::Failure {}
+
+impl<E: Error + 'static, W: fmt::Display + fmt::Debug + 'static> ErrorExitCode
+  for Contextual<E, W>
+{
+}
+
+/// The default exit code for an error. If `error` or any error in its
+/// source chain is an `io::Error`, a `sysexits.h`-style code based on
+/// its `ErrorKind`, otherwise `1`.
+pub fn exit_code(error: &(dyn Error + 'static)) -> u8 {
+  let mut source = Some(error);
+
+  while let Some(error) = source {
+    if let Some(io_error) = error.downcast_ref::<io::Error>() {
+      return match io_error.kind() {
+        ErrorKind::InvalidInput => 64,
+        ErrorKind::InvalidData | ErrorKind::UnexpectedEof => 65,
+        ErrorKind::NotFound => 66,
+        ErrorKind::ConnectionRefused
+        | ErrorKind::ConnectionReset
+        | ErrorKind::ConnectionAborted
+        | ErrorKind::NotConnected
+        | ErrorKind::AddrNotAvailable => 69,
+        ErrorKind::AlreadyExists => 73,
+        ErrorKind::Interrupted | ErrorKind::TimedOut | ErrorKind::WouldBlock => 75,
+        ErrorKind::PermissionDenied => 77,
+        _ => 74,
+      };
+    }
+
+    source = error.source();
+  }
+
+  1
+}
+
+/// Greedily wrap `line` at spaces into pieces no longer than `width`
+/// characters, except for words which are themselves too long. Leading
+/// whitespace is preserved on the first piece.
+fn wrap(line: &str, width: usize) -> Vec<&str> {
+  let mut pieces = Vec::new();
+  let mut rest = line;
+
+  loop {
+    let (end, c) = match rest.char_indices().nth(width) {
+      Some(next) => next,
+      None => {
+        pieces.push(rest);
+        break;
+      }
+    };
+
+    let indent = (rest.len() - rest.trim_start().len()).min(end);
+
+    let split = if c == ' ' {
+      end
+    } else {
+      rest[indent..end]
+        .rfind(' ')
+        .map(|space| indent + space)
+        .or_else(|| rest[end..].find(' ').map(|space| end + space))
+        .unwrap_or(rest.len())
+    };
+
+    pieces.push(&rest[..split]);
+    rest = rest[split..].trim_start_matches(' ');
+
+    if rest.is_empty() {
+      break;
+    }
+  }
+
+  pieces
+}
@@ -0,0 +1,275 @@
+//! A thread-local stack of ambient context frames, for attaching
+//! context to errors in code which cannot easily pass it explicitly.
+//! Enabled by the `std` feature.
+//!
+//! Frames are pushed with `scope`, which returns a guard that pops the
+//! frame when dropped, and the current frames are attached to errors
+//! with `ErrWithScope::err_with_scope`:
+//!
+//! ```
+//! use std::{fs, io};
+//!
+//! use err_with::{scope, ErrWithScope};
+//!
+//! fn read() -> Result<String, (io::Error, scope::Scopes)> {
+//!   let _guard = scope("reading foo/bar");
+//!   fs::read_to_string("foo/bar").err_with_scope()
+//! }
+//!
+//! fn load() -> Result<String, (io::Error, scope::Scopes)> {
+//!   let _guard = scope("loading config");
+//!   read()
+//! }
+//!
+//! let (_, scopes) = load().unwrap_err();
+//! assert_eq!(scopes.to_string(), "loading config: reading foo/bar");
+//! assert!(scope::current().is_empty());
+//! ```
+//!
+//! Since asynchronous tasks may be polled on different threads, and
+//! interleaved on the same thread, guards returned by `scope` should
+//! not be held across `.await` points. Instead, `InContext::in_context`
+//! pushes a frame only while a future is being polled.
+
+use std::{
+  borrow::Borrow,
+  cell::RefCell,
+  fmt,
+  future::Future,
+  marker::PhantomData,
+  pin::Pin,
+  string::String,
+  sync::Arc,
+  task::{Context, Poll},
+  thread_local,
+  vec::Vec,
+};
+
+use pin_project_lite::pin_project;
+
+use crate::ErrWith;
+
+thread_local! {
+  static FRAMES: RefCell<Vec<Frame>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Push `frame` onto the current thread's scope stack, returning a
+/// guard which pops it, and any frames pushed after it, when dropped.
+pub fn scope<F: Into<Frame>>(frame: F) -> ScopeGuard {
+  ScopeGuard {
+    depth: push(frame.into()),
+    _thread: PhantomData,
+  }
+}
+
+/// A snapshot of the current thread's scope stack.
+pub fn current() -> Scopes {
+  FRAMES.with(|frames| Scopes(frames.borrow().clone()))
+}
+
+fn push(frame: Frame) -> usize {
+  FRAMES.with(|frames| {
+    let mut frames = frames.borrow_mut();
+    frames.push(frame);
+    frames.len() - 1
+  })
+}
+
+fn truncate(depth: usize) {
+  FRAMES.with(|frames| frames.borrow_mut().truncate(depth));
+}
+
+/// Extension trait for `ErrWith` implementors, enabled by the `std`
+/// feature.
+pub trait ErrWithScope<T, E> {
+  /// Attach a snapshot of the current thread's scope stack, as pushed
+  /// by `scope` or `InContext::in_context`. The snapshot is only taken
+  /// on the `Err` path.
+  fn err_with_scope(self) -> Result<T, (E, Scopes)>;
+}
+
+impl<T, E, R: ErrWith<T, E>> ErrWithScope<T, E> for R {
+  fn err_with_scope(self) -> Result<T, (E, Scopes)> {
+    self.err_with_lazy(current)
+  }
+}
+
+/// Extension trait for futures, installing a scope frame while the
+/// future is polled, and removing it between polls, so that errors
+/// converted with `ErrWithScope::err_with_scope` inside the future capture
+/// its logical stack of frames, regardless of the executor or thread
+/// it runs on:
+///
+/// ```
+/// use std::{
+///   future::Future,
+///   pin::pin,
+///   task::{Context, Poll, Waker},
+/// };
+///
+/// use err_with::{scope::{self, InContext}, ErrWithScope};
+///
+/// fn block_on<F: Future>(future: F) -> F::Output {
+///   let mut future = pin!(future);
+///   let mut cx = Context::from_waker(Waker::noop());
+///   loop {
+///     if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+///       return output;
+///     }
+///     assert!(scope::current().is_empty());
+///   }
+/// }
+///
+/// async fn yield_now() {
+///   let mut yielded = false;
+///   std::future::poll_fn(|_| {
+///     if yielded {
+///       Poll::Ready(())
+///     } else {
+///       yielded = true;
+///       Poll::Pending
+///     }
+///   })
+///   .await
+/// }
+///
+/// async fn read() -> Result<(), (&'static str, scope::Scopes)> {
+///   yield_now().await;
+///   Err("failed").err_with_scope()
+/// }
+///
+/// let future = async { read().in_context("reading").await }.in_context("loading");
+///
+/// let (_, scopes) = block_on(future).unwrap_err();
+/// assert_eq!(scopes.to_string(), "loading: reading");
+/// ```
+pub trait InContext: Future + Sized {
+  fn in_context<F: Into<Frame>>(self, frame: F) -> InContextFuture<Self> {
+    InContextFuture {
+      future: self,
+      frame: frame.into(),
+    }
+  }
+}
+
+impl<F: Future> InContext for F {}
+
+pin_project! {
+  /// Future returned by `InContext::in_context`.
+  #[derive(Debug)]
+  #[must_use = "futures do nothing unless polled"]
+  pub struct InContextFuture<F> {
+    #[pin]
+    future: F,
+    frame: Frame,
+  }
+}
+
+impl<F: Future> Future for InContextFuture<F> {
+  type Output = F::Output;
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+    let this = self.project();
+
+    let _guard = scope(this.frame.clone());
+
+    this.future.poll(cx)
+  }
+}
+
+/// Pops a frame from the scope stack when dropped. Returned by `scope`.
+#[derive(Debug)]
+#[must_use = "the frame is popped when the guard is dropped"]
+pub struct ScopeGuard {
+  depth: usize,
+  _thread: PhantomData<*const ()>,
+}
+
+impl Drop for ScopeGuard {
+  fn drop(&mut self) {
+    truncate(self.depth);
+  }
+}
+
+/// A frame in a scope stack, either a `&'static str` or an `Arc<str>`,
+/// so that frames are cheap to push and to clone.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Frame(Repr);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Repr {
+  Static(&'static str),
+  Shared(Arc<str>),
+}
+
+impl Frame {
+  pub fn as_str(&self) -> &str {
+    match &self.0 {
+      Repr::Static(frame) => frame,
+      Repr::Shared(frame) => frame,
+    }
+  }
+}
+
+impl From<&'static str> for Frame {
+  fn from(frame: &'static str) -> Frame {
+    Frame(Repr::Static(frame))
+  }
+}
+
+impl From<Arc<str>> for Frame {
+  fn from(frame: Arc<str>) -> Frame {
+    Frame(Repr::Shared(frame))
+  }
+}
+
+impl From<String> for Frame {
+  fn from(frame: String) -> Frame {
+    Frame(Repr::Shared(frame.into()))
+  }
+}
+
+impl Borrow<str> for Frame {
+  fn borrow(&self) -> &str {
+    self.as_str()
+  }
+}
+
+impl fmt::Display for Frame {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}", self.as_str())
+  }
+}
+
+/// A snapshot of a scope stack, outermost frame first, produced by
+/// `ErrWithScope::err_with_scope`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Scopes(Vec<Frame>);
+
+impl Scopes {
+  pub fn frames(&self) -> &[Frame] {
+    &self.0
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.0.is_empty()
+  }
+
+  pub fn len(&self) -> usize {
+    self.0.len()
+  }
+}
+
+/// Renders the frames separated by `: `, outermost first.
+impl fmt::Display for Scopes {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    for (i, frame) in self.0.iter().enumerate() {
+      if i > 0 {
+        write!(f, ": ")?;
+      }
+      write!(f, "{}", frame)?;
+    }
+
+    Ok(())
+  }
+}
@@ -0,0 +1,328 @@
+use std::{
+  boxed::Box,
+  error::Error,
+  fmt,
+  panic::Location,
+  string::{String, ToString},
+  vec::Vec,
+};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{AnyContextual, Conditional, Contextual, Located};
+
+/// A serializable snapshot of an error and its context, enabled by the
+/// `serde` feature.
+///
+/// Snapshots serialize with a stable schema: `message`, the error's
+/// `Display` output, `context`, the serialized context, `sources`, the
+/// `Display` output of each error in the source chain, and `location`,
+/// the location the context was attached, if known. They can be
+/// deserialized into a `RemoteError`:
+///
+/// ```
+/// use std::{error::Error, fs};
+///
+/// use err_with::{Contextual, ErrWith, RemoteError, Snapshot};
+///
+/// let (error, path) = fs::read("foo/bar").err_with("foo/bar").unwrap_err();
+///
+/// let json = serde_json::to_string(&Snapshot::new(&error, &path)).unwrap();
+///
+/// let remote: RemoteError<String> = serde_json::from_str(&json).unwrap();
+/// assert_eq!(remote.to_string(), "foo/bar");
+/// assert_eq!(remote.message(), error.to_string());
+/// assert_eq!(remote.source().unwrap().to_string(), error.to_string());
+/// assert!(remote.location().is_none());
+///
+/// let contextual = Contextual::new(Contextual::new(error, "reading"), "loading");
+/// let json = serde_json::to_string(&contextual).unwrap();
+///
+/// let remote: RemoteError<String> = serde_json::from_str(&json).unwrap();
+/// assert_eq!(remote.to_string(), "loading");
+/// assert_eq!(remote.message(), "reading");
+/// assert_eq!(
+///   remote.source().unwrap().source().unwrap().to_string(),
+///   contextual.error.error.to_string(),
+/// );
+/// ```
+#[derive(Debug, Clone, Serialize)]
+pub struct Snapshot<'a, W: ?Sized> {
+  message: String,
+  context: &'a W,
+  sources: Vec<String>,
+  location: Option<LocationSnapshot>,
+}
+
+impl<'a, W: ?Sized> Snapshot<'a, W> {
+  pub fn new<E: Error + ?Sized>(error: &E, context: &'a W) -> Snapshot<'a, W> {
+    let mut sources = Vec::new();
+    let mut source = error.source();
+
+    while let Some(error) = source {
+      sources.push(error.to_string());
+      source = error.source();
+    }
+
+    Snapshot {
+      message: error.to_string(),
+      context,
+      sources,
+      location: None,
+    }
+  }
+}
+
+impl<'a, W> Snapshot<'a, W> {
+  /// Snapshot an error and a context produced by `ErrWith::err_here`,
+  /// recording the context's location.
+  pub fn located<E: Error + ?Sized>(error: &E, located: &'a Located<W>) -> Snapshot<'a, W> {
+    Snapshot {
+      location: Some(located.location().into()),
+      ..Snapshot::new(error, located.context())
+    }
+  }
+}
+
+impl<'a, E: Error, W> From<&'a (E, W)> for Snapshot<'a, W> {
+  fn from((error, context): &'a (E, W)) -> Snapshot<'a, W> {
+    Snapshot::new(error, context)
+  }
+}
+
+impl<E: Error, W: Serialize> Serialize for Contextual<E, W> {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    Snapshot::new(&self.error, &self.context).serialize(serializer)
+  }
+}
+
+/// Serializes with a `null` context for `Conditional::Plain`, so both
+/// variants can be deserialized into a `RemoteError<Option<W>>`:
+///
+/// ```
+/// use err_with::{Conditional, RemoteError};
+///
+/// let plain = Conditional::<_, &str>::Plain(err_with::Missing);
+/// let json = serde_json::to_string(&plain).unwrap();
+/// let remote: RemoteError<Option<String>> = serde_json::from_str(&json).unwrap();
+/// assert_eq!(remote.context(), &None);
+///
+/// let with = Conditional::With(err_with::Missing, "foo/bar");
+/// let json = serde_json::to_string(&with).unwrap();
+/// let remote: RemoteError<Option<String>> = serde_json::from_str(&json).unwrap();
+/// assert_eq!(remote.context().as_deref(), Some("foo/bar"));
+/// assert_eq!(remote.message(), "missing value");
+/// ```
+impl<E: Error, W: Serialize> Serialize for Conditional<E, W> {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    Snapshot::new(self.error(), &self.context()).serialize(serializer)
+  }
+}
+
+/// Serializes with the context as its rendered string, since its type
+/// is erased:
+///
+/// ```
+/// use std::path::PathBuf;
+///
+/// use err_with::{ErrWithAny, RemoteError};
+///
+/// let error = None::<()>.err_with_any_debug(PathBuf::from("foo/bar")).unwrap_err();
+/// let json = serde_json::to_string(&error).unwrap();
+///
+/// let remote: RemoteError<String> = serde_json::from_str(&json).unwrap();
+/// assert_eq!(remote.to_string(), error.to_string());
+/// assert_eq!(remote.message(), "missing value");
+/// ```
+impl Serialize for AnyContextual {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    let context = self.to_string();
+    Snapshot::new(self.error(), &context).serialize(serializer)
+  }
+}
+
+/// Serializes with the location lifted into the top-level `location`
+/// field, as with `Snapshot::located`:
+///
+/// ```
+/// use err_with::{Contextual, ErrWith, RemoteError};
+/// use serde_json::json;
+///
+/// let (error, located) = None::<u8>.err_here("foo/bar").unwrap_err();
+/// let (line, column) = (located.line(), located.column());
+///
+/// let contextual = Contextual::new(error, located);
+///
+/// assert_eq!(
+///   serde_json::to_value(&contextual).unwrap(),
+///   json!({
+///     "message": "missing value",
+///     "context": "foo/bar",
+///     "sources": [],
+///     "location": { "file": file!(), "line": line, "column": column },
+///   }),
+/// );
+///
+/// let json = serde_json::to_string(&contextual).unwrap();
+/// let remote: RemoteError<String> = serde_json::from_str(&json).unwrap();
+/// assert_eq!(remote.context(), "foo/bar");
+/// assert_eq!(remote.location().unwrap().line, line);
+/// ```
+impl<E: Error, W: Serialize> Serialize for Contextual<E, Located<W>> {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    Snapshot::located(&self.error, &self.context).serialize(serializer)
+  }
+}
+
+/// The serialized form of a `Location`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct LocationSnapshot {
+  pub file: String,
+  pub line: u32,
+  pub column: u32,
+}
+
+impl From<&Location<'_>> for LocationSnapshot {
+  fn from(location: &Location) -> LocationSnapshot {
+    LocationSnapshot {
+      file: location.file().to_string(),
+      line: location.line(),
+      column: location.column(),
+    }
+  }
+}
+
+impl fmt::Display for LocationSnapshot {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}:{}:{}", self.file, self.line, self.column)
+  }
+}
+
+/// An error reconstructed from a serialized `Snapshot`. Like a
+/// `Contextual`, it displays its context, and its source chain starts
+/// with the original error's message, so that it renders the same
+/// report as the error it was serialized from:
+///
+/// ```
+/// use std::io;
+///
+/// use err_with::{report::Report, Contextual, RemoteError};
+///
+/// let error = Contextual::new(
+///   Contextual::new(io::Error::new(io::ErrorKind::NotFound, "not found"), "foo/bar"),
+///   "loading",
+/// );
+///
+/// let json = serde_json::to_string(&error).unwrap();
+/// let remote: RemoteError<String> = serde_json::from_str(&json).unwrap();
+///
+/// assert_eq!(Report::new(&remote).to_string(), Report::new(&error).to_string());
+/// assert_eq!(
+///   Report::new(&remote).to_string(),
+///   "error: loading\ncaused by:\n  1: foo/bar\n  2: not found",
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct RemoteError<W> {
+  error: RemoteSource,
+  context: W,
+  location: Option<LocationSnapshot>,
+}
+
+impl<W> RemoteError<W> {
+  /// The `Display` output of the original error.
+  pub fn message(&self) -> &str {
+    &self.error.message
+  }
+
+  pub fn context(&self) -> &W {
+    &self.context
+  }
+
+  pub fn into_context(self) -> W {
+    self.context
+  }
+
+  pub fn location(&self) -> Option<&LocationSnapshot> {
+    self.location.as_ref()
+  }
+}
+
+impl<W: fmt::Display> fmt::Display for RemoteError<W> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    fmt::Display::fmt(&self.context, f)
+  }
+}
+
+impl<W: fmt::Display + fmt::Debug> Error for RemoteError<W> {
+  fn source(&self) -> Option<&(dyn Error + 'static)> {
+    Some(&self.error)
+  }
+}
+
+/// Serializes with the same schema as `Snapshot`.
+impl<W: Serialize> Serialize for RemoteError<W> {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    Snapshot {
+      location: self.location.clone(),
+      ..Snapshot::new(&self.error, &self.context)
+    }
+    .serialize(serializer)
+  }
+}
+
+impl<'de, W: Deserialize<'de>> Deserialize<'de> for RemoteError<W> {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    #[derive(Deserialize)]
+    struct Fields<W> {
+      message: String,
+      context: W,
+      sources: Vec<String>,
+      location: Option<LocationSnapshot>,
+    }
+
+    let fields = Fields::deserialize(deserializer)?;
+
+    let source = fields
+      .sources
+      .into_iter()
+      .rev()
+      .fold(None, |source, message| {
+        Some(Box::new(RemoteSource { message, source }))
+      });
+
+    Ok(RemoteError {
+      error: RemoteSource {
+        message: fields.message,
+        source,
+      },
+      context: fields.context,
+      location: fields.location,
+    })
+  }
+}
+
+/// An error in the source chain of a `RemoteError`.
+#[derive(Debug, Clone)]
+pub struct RemoteSource {
+  message: String,
+  source: Option<Box<RemoteSource>>,
+}
+
+impl RemoteSource {
+  pub fn message(&self) -> &str {
+    &self.message
+  }
+}
+
+impl fmt::Display for RemoteSource {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}", self.message)
+  }
+}
+
+impl Error for RemoteSource {
+  fn source(&self) -> Option<&(dyn Error + 'static)> {
+    self.source.as_ref().map(|source| source.as_ref() as _)
+  }
+}
@@ -0,0 +1,119 @@
+use core::any::Any;
+
+/// A flat list of contexts, stored as a tuple, from which contexts
+/// can be extracted by type.
+pub trait Contexts {
+  /// Get the first context of type `W`, if any.
+  fn get<W: Any>(&self) -> Option<&W>;
+}
+
+/// Append a context to a tuple of contexts, producing a tuple one
+/// element longer.
+pub trait Push<W> {
+  type Output;
+
+  fn push(self, with: W) -> Self::Output;
+}
+
+/// Add contexts to a `Result<T, (E, C)>`, where `C` is a tuple of
+/// contexts, without nesting.
+///
+/// A stack is started with `err_with` and a tuple, usually a
+/// one-element tuple, and further contexts are pushed with
+/// `stack_with`. Downstream conversions can be written for any
+/// `C: Contexts`, and will not need to change as contexts are added:
+///
+/// ```
+/// use std::{fs, io, path::{Path, PathBuf}};
+///
+/// use err_with::{Contexts, ErrWith, StackWith};
+///
+/// #[derive(Debug)]
+/// struct Error {
+///   io_error: io::Error,
+///   path: Option<PathBuf>,
+///   operation: Option<&'static str>,
+/// }
+///
+/// impl<C: Contexts> From<(io::Error, C)> for Error {
+///   fn from((io_error, contexts): (io::Error, C)) -> Error {
+///     Error {
+///       io_error,
+///       path: contexts.get::<PathBuf>().cloned(),
+///       operation: contexts.get::<&str>().cloned(),
+///     }
+///   }
+/// }
+///
+/// fn read(path: &Path) -> Result<String, (io::Error, (PathBuf,))> {
+///   fs::read_to_string(path).err_with((path.to_owned(),))
+/// }
+///
+/// fn load() -> Result<String, Error> {
+///   Ok(read(Path::new("foo/bar")).stack_with("loading config")?)
+/// }
+///
+/// let error = load().unwrap_err();
+/// assert_eq!(error.path.unwrap(), Path::new("foo/bar"));
+/// assert_eq!(error.operation, Some("loading config"));
+/// ```
+pub trait StackWith<T, E, C> {
+  fn stack_with<W>(self, with: W) -> Result<T, (E, C::Output)>
+  where
+    C: Push<W>;
+}
+
+impl<T, E, C> StackWith<T, E, C> for Result<T, (E, C)> {
+  fn stack_with<W>(self, with: W) -> Result<T, (E, C::Output)>
+  where
+    C: Push<W>,
+  {
+    self.map_err(|(error, contexts)| (error, contexts.push(with)))
+  }
+}
+
+macro_rules! contexts {
+  ($($name:ident $index:tt),*) => {
+    impl<$($name: Any),*> Contexts for ($($name,)*) {
+      fn get<W: Any>(&self) -> Option<&W> {
+        $(
+          if let Some(with) = (&self.$index as &dyn Any).downcast_ref::<W>() {
+            return Some(with);
+          }
+        )*
+        None
+      }
+    }
+  };
+}
+
+macro_rules! push {
+  ($($name:ident $index:tt),*) => {
+    impl<$($name,)* W> Push<W> for ($($name,)*) {
+      type Output = ($($name,)* W,);
+
+      fn push(self, with: W) -> Self::Output {
+        ($(self.$index,)* with,)
+      }
+    }
+  };
+}
+
+contexts!();
+contexts!(A 0);
+contexts!(A 0, B 1);
+contexts!(A 0, B 1, C 2);
+contexts!(A 0, B 1, C 2, D 3);
+contexts!(A 0, B 1, C 2, D 3, F 4);
+contexts!(A 0, B 1, C 2, D 3, F 4, G 5);
+contexts!(A 0, B 1, C 2, D 3, F 4, G 5, H 6);
+contexts!(A 0, B 1, C 2, D 3, F 4, G 5, H 6, I 7);
+
+push!();
+push!(A 0);
+push!(A 0, B 1);
+push!(A 0, B 1, C 2);
+push!(A 0, B 1, C 2, D 3);
+push!(A 0, B 1, C 2, D 3, F 4);
+push!(A 0, B 1, C 2, D 3, F 4, G 5);
+push!(A 0, B 1, C 2, D 3, F 4, G 5, H 6);
@@ -0,0 +1,114 @@
+use core::{
+  pin::Pin,
+  task::{Context, Poll},
+};
+
+use futures_core::Stream;
+use pin_project_lite::pin_project;
+
+use crate::ErrWith;
+
+/// Extension trait for streams of `Result<T, E>`, the asynchronous
+/// counterpart of `ErrWithIter`, enabled by the `stream` feature:
+///
+/// ```
+/// use futures::{executor::block_on, stream, StreamExt};
+///
+/// use err_with::ErrWithStream;
+///
+/// let results = stream::iter(vec![Ok(1), Err("bad")]);
+///
+/// assert_eq!(
+///   block_on(results.err_with_index(|i| i).collect::<Vec<_>>()),
+///   vec![Ok(1), Err(("bad", 1))],
+/// );
+/// ```
+pub trait ErrWithStream: Stream + Sized {
+  /// Attach a clone of `with` to every error.
+  fn err_with<W: Clone>(self, with: W) -> WithStream<Self, W> {
+    WithStream { stream: self, with }
+  }
+
+  /// Attach the result of calling `f` with the index of each error in
+  /// the stream, counting both `Ok` and `Err` items.
+  fn err_with_index<F>(self, f: F) -> WithIndexStream<Self, F> {
+    WithIndexStream {
+      stream: self,
+      f,
+      index: 0,
+    }
+  }
+}
+
+impl<S: Stream> ErrWithStream for S {}
+
+pin_project! {
+  /// Stream returned by `ErrWithStream::err_with`.
+  #[derive(Debug, Clone)]
+  #[must_use = "streams do nothing unless polled"]
+  pub struct WithStream<S, W> {
+    #[pin]
+    stream: S,
+    with: W,
+  }
+}
+
+impl<S, W, T, E> Stream for WithStream<S, W>
+where
+  S: Stream<Item = Result<T, E>>,
+  W: Clone,
+{
+  type Item = Result<T, (E, W)>;
+
+  fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+    let this = self.project();
+    let with = this.with;
+
+    this
+      .stream
+      .poll_next(cx)
+      .map(|item| item.map(|result| result.err_with_lazy(|| with.clone())))
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    self.stream.size_hint()
+  }
+}
+
+pin_project! {
+  /// Stream returned by `ErrWithStream::err_with_index`.
+  #[derive(Debug, Clone)]
+  #[must_use = "streams do nothing unless polled"]
+  pub struct WithIndexStream<S, F> {
+    #[pin]
+    stream: S,
+    f: F,
+    index: usize,
+  }
+}
+
+impl<S, F, W, T, E> Stream for WithIndexStream<S, F>
+where
+  S: Stream<Item = Result<T, E>>,
+  F: FnMut(usize) -> W,
+{
+  type Item = Result<T, (E, W)>;
+
+  fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+    let this = self.project();
+    let index = this.index;
+    let f = this.f;
+
+    this.stream.poll_next(cx).map(|item| {
+      item.map(|result| {
+        let i = *index;
+        *index += 1;
+        result.err_with_lazy(|| f(i))
+      })
+    })
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    self.stream.size_hint()
+  }
+}
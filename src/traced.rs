@@ -0,0 +1,90 @@
+use std::{
+  backtrace::{Backtrace, BacktraceStatus},
+  fmt,
+};
+
+use crate::ErrWith;
+
+/// Extension trait for `ErrWith` implementors, enabled by the
+/// `backtrace` feature.
+pub trait ErrWithBacktrace<T, E> {
+  /// Like `err_with`, but captures a backtrace in a `Traced` wrapping
+  /// the context. The backtrace is only captured on the `Err` path.
+  fn err_with_backtrace<W>(self, with: W) -> Result<T, (E, Traced<W>)>;
+}
+
+impl<T, E, R: ErrWith<T, E>> ErrWithBacktrace<T, E> for R {
+  fn err_with_backtrace<W>(self, with: W) -> Result<T, (E, Traced<W>)> {
+    self.err_with_lazy(|| Traced::new(with))
+  }
+}
+
+/// A context with a backtrace captured when it was attached, produced
+/// by `ErrWithBacktrace::err_with_backtrace` and enabled by the
+/// `backtrace` feature.
+///
+/// The backtrace is captured with `Backtrace::capture`, and so is only
+/// captured if enabled with the `RUST_BACKTRACE` or
+/// `RUST_LIB_BACKTRACE` environment variables. `Display` renders only
+/// the context, and the alternate form, `{:#}`, additionally renders
+/// the backtrace, if one was captured, or, in a `Report` with details,
+/// moves it to a `backtrace:` section:
+///
+/// ```
+/// use std::backtrace::BacktraceStatus;
+///
+/// use err_with::ErrWithBacktrace;
+///
+/// let (_, traced) = Err::<(), _>("error")
+///   .err_with_backtrace("context")
+///   .unwrap_err();
+///
+/// assert_eq!(traced.context(), &"context");
+/// assert_eq!(traced.to_string(), "context");
+///
+/// if traced.backtrace().status() == BacktraceStatus::Captured {
+///   assert!(format!("{:#}", traced).contains("stack backtrace:"));
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Traced<W> {
+  context: W,
+  backtrace: Backtrace,
+}
+
+impl<W> Traced<W> {
+  /// Attach a backtrace of the current stack to `context`.
+  pub fn new(context: W) -> Traced<W> {
+    Traced {
+      context,
+      backtrace: Backtrace::capture(),
+    }
+  }
+
+  pub fn context(&self) -> &W {
+    &self.context
+  }
+
+  pub fn into_context(self) -> W {
+    self.context
+  }
+
+  pub fn backtrace(&self) -> &Backtrace {
+    &self.backtrace
+  }
+}
+
+impl<W: fmt::Display> fmt::Display for Traced<W> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}", self.context)?;
+
+    if f.alternate()
+      && self.backtrace.status() == BacktraceStatus::Captured
+      && !crate::report::record_backtrace(&self.backtrace)
+    {
+      write!(f, "\n\nstack backtrace:\n{}", self.backtrace)?;
+    }
+
+    Ok(())
+  }
+}
@@ -0,0 +1,33 @@
+use core::future::Future;
+
+use crate::{future::WithFuture, ErrWith, ErrWithFuture};
+
+/// Run `f`, attaching `with` to any error it returns. Equivalent to
+/// `f().err_with(with)`, allowing a single context to be attached to
+/// every `?` in a logical operation:
+///
+/// ```
+/// use std::{fs, io};
+///
+/// use err_with::try_with;
+///
+/// let (_, context) = try_with("loading config", || -> io::Result<String> {
+///   let path = fs::read_to_string("foo/path")?;
+///   fs::read_to_string(path.trim())
+/// })
+/// .unwrap_err();
+///
+/// assert_eq!(context, "loading config");
+/// ```
+///
+/// Since the closure's error type is not constrained by the `?`s
+/// inside of it, it may need to be annotated, as above.
+pub fn try_with<T, E, W, F: FnOnce() -> Result<T, E>>(with: W, f: F) -> Result<T, (E, W)> {
+  f().err_with(with)
+}
+
+/// Attach `with` to any error returned by `future`, typically an
+/// `async` block. Equivalent to `future.err_with(with)`.
+pub fn try_with_async<W, F: Future>(with: W, future: F) -> WithFuture<F, W> {
+  future.err_with(with)
+}
@@ -0,0 +1,52 @@
+use std::{env, fs, process::Command};
+
+fn run(example: &str) -> (String, Option<i32>) {
+  let path = env::current_exe()
+    .unwrap()
+    .parent()
+    .unwrap()
+    .parent()
+    .unwrap()
+    .join("examples")
+    .join(example);
+
+  let output = Command::new(path)
+    .env("NO_COLOR", "1")
+    .env("RUST_BACKTRACE", "0")
+    .env("RUST_LIB_BACKTRACE", "0")
+    .env_remove("COLUMNS")
+    .output()
+    .unwrap();
+
+  assert!(output.stdout.is_empty());
+
+  (
+    String::from_utf8(output.stderr).unwrap(),
+    output.status.code(),
+  )
+}
+
+#[test]
+fn not_found() {
+  let (stderr, code) = run("not-found");
+
+  assert_eq!(
+    stderr,
+    format!(
+      "error: loading configuration\ncaused by:\n  1: {}\n",
+      fs::read("does/not/exist").unwrap_err(),
+    ),
+  );
+  assert_eq!(code, Some(66));
+}
+
+#[test]
+fn boxed() {
+  let (stderr, code) = run("boxed");
+
+  assert_eq!(
+    stderr,
+    "error: parsing port\ncaused by:\n  1: invalid digit found in string\n",
+  );
+  assert_eq!(code, Some(1));
+}